@@ -1,9 +1,15 @@
-use clap::{Arg, ArgMatches, Command};
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use mooncats::coverage::CoverageReport;
+use mooncats::dump::dump_docs;
+use mooncats::generate::{generate, load_docs, DocOptions, PathRemap};
 use mooncats::mdbook::MoonCats;
 use mdbook::errors::Error;
 use mdbook::preprocess::{CmdPreprocessor, Preprocessor};
 use semver::{Version, VersionReq};
+use std::env;
+use std::fs::File;
 use std::io;
+use std::path::PathBuf;
 use std::process;
 
 pub fn make_app() -> Command {
@@ -14,6 +20,141 @@ pub fn make_app() -> Command {
                 .arg(Arg::new("renderer").required(true))
                 .about("Check whether a renderer is supported by this preprocessor"),
         )
+        .subcommand(
+            library_args(Command::new("generate"))
+                .about("Write the API docs to a directory of markdown files, without mdbook")
+                .arg(
+                    Arg::new("out")
+                        .long("out")
+                        .required(true)
+                        .value_parser(clap::value_parser!(PathBuf))
+                        .help("The directory to write the markdown files to"),
+                )
+                .arg(
+                    Arg::new("inherited-members")
+                        .long("inherited-members")
+                        .action(ArgAction::SetTrue)
+                        .help("List the members each class inherits"),
+                )
+                .arg(
+                    Arg::new("item-order")
+                        .long("item-order")
+                        .value_parser(["source", "alphabetical"])
+                        .default_value("source")
+                        .help("The order to list items and members in"),
+                )
+                .arg(
+                    Arg::new("template-dir")
+                        .long("template-dir")
+                        .value_parser(clap::value_parser!(PathBuf))
+                        .help("A directory of .hbs files replacing the built-in templates"),
+                )
+                .arg(
+                    Arg::new("page-granularity")
+                        .long("page-granularity")
+                        .value_parser(["file", "item"])
+                        .default_value("file")
+                        .help("Write a page for each file, or also for each class, table and enum"),
+                )
+                .arg(
+                    Arg::new("index-chapters")
+                        .long("index-chapters")
+                        .action(ArgAction::SetTrue)
+                        .help("Write pages listing every class, function, type, enum and global"),
+                ),
+        )
+        .subcommand(
+            library_args(Command::new("check"))
+                .about("Report undocumented API, failing when coverage is below a threshold")
+                .arg(
+                    Arg::new("min-coverage")
+                        .long("min-coverage")
+                        .value_name("PERCENT")
+                        .value_parser(clap::value_parser!(f64))
+                        .default_value("100")
+                        .help("The minimum coverage of the whole library"),
+                )
+                .arg(
+                    Arg::new("min-file-coverage")
+                        .long("min-file-coverage")
+                        .value_name("PERCENT")
+                        .value_parser(clap::value_parser!(f64))
+                        .help("The minimum coverage of each file"),
+                )
+                .arg(
+                    Arg::new("json")
+                        .long("json")
+                        .action(ArgAction::SetTrue)
+                        .help("Print the coverage report as JSON"),
+                ),
+        )
+        .subcommand(
+            library_args(Command::new("dump"))
+                .about("Write the processed docs as versioned JSON")
+                .arg(
+                    Arg::new("out")
+                        .long("out")
+                        .value_parser(clap::value_parser!(PathBuf))
+                        .help("The file to write to, instead of stdout"),
+                )
+                .arg(
+                    Arg::new("inherited-members")
+                        .long("inherited-members")
+                        .action(ArgAction::SetTrue)
+                        .help("List the members each class inherits"),
+                )
+                .arg(
+                    Arg::new("item-order")
+                        .long("item-order")
+                        .value_parser(["source", "alphabetical"])
+                        .default_value("source")
+                        .help("The order to list items and members in"),
+                ),
+        )
+}
+
+/// Add the arguments for reading a library's definitions.
+fn library_args(command: Command) -> Command {
+    command
+        .arg(
+            Arg::new("library")
+                .long("library")
+                .required(true)
+                .value_parser(clap::value_parser!(PathBuf))
+                .help("The directory containing the LuaCATS definitions"),
+        )
+        .arg(
+            Arg::new("doc-json")
+                .long("doc-json")
+                .value_parser(clap::value_parser!(PathBuf))
+                .help("Read a doc.json exported by LuaLS instead of running it"),
+        )
+        .arg(
+            Arg::new("path-remap")
+                .long("path-remap")
+                .value_name("FROM=TO")
+                .action(ArgAction::Append)
+                .help("Rewrite definition file paths starting with FROM to start with TO"),
+        )
+        .arg(
+            Arg::new("strict")
+                .long("strict")
+                .action(ArgAction::SetTrue)
+                .help("Fail on warnings about the definitions, as well as errors"),
+        )
+        .arg(
+            Arg::new("visibility")
+                .long("visibility")
+                .value_parser(["public", "protected", "all"])
+                .default_value("public")
+                .help("The members to document, by their visibility"),
+        )
+        .arg(
+            Arg::new("hide-underscored")
+                .long("hide-underscored")
+                .action(ArgAction::SetTrue)
+                .help("Leave out members with a name starting with _"),
+        )
 }
 
 fn main() {
@@ -25,7 +166,29 @@ fn main() {
 
     match matches.subcommand() {
         Some(("supports", subargs)) => handle_supports(&preprocessor, subargs),
-        Some((cmd, _)) => eprintln!("unknown subcommand {}", cmd),
+        Some(("generate", subargs)) => {
+            if let Err(e) = handle_generate(subargs) {
+                eprintln!("{}", e);
+                process::exit(1);
+            }
+        }
+        Some(("dump", subargs)) => {
+            if let Err(e) = handle_dump(subargs) {
+                eprintln!("{}", e);
+                process::exit(1);
+            }
+        }
+        Some(("check", subargs)) => match handle_check(subargs) {
+            Ok(passed) => process::exit(if passed { 0 } else { 1 }),
+            Err(e) => {
+                eprintln!("{}", e);
+                process::exit(2);
+            }
+        },
+        Some((cmd, _)) => {
+            eprintln!("unknown subcommand {}", cmd);
+            process::exit(1);
+        }
         None => {
             if let Err(e) = handle_preprocessing(&preprocessor) {
                 eprintln!("{}", e);
@@ -69,3 +232,119 @@ fn handle_supports(pre: &dyn Preprocessor, sub_args: &ArgMatches) -> ! {
         process::exit(1);
     }
 }
+
+/// Read the library path and doc options from the library arguments.
+fn library_options(sub_args: &ArgMatches) -> Result<(PathBuf, DocOptions), Error> {
+    let cwd = env::current_dir()?;
+    let library = cwd.join(sub_args.get_one::<PathBuf>("library").expect("Required argument"));
+
+    let path_remaps = sub_args
+        .get_many::<String>("path-remap")
+        .unwrap_or_default()
+        .map(|remap| {
+            let (from, to) = remap
+                .split_once('=')
+                .ok_or_else(|| Error::msg(format!("Invalid path remap {:?}, expected FROM=TO", remap)))?;
+            Ok(PathRemap::new(from, cwd.join(to)))
+        })
+        .collect::<Result<Vec<PathRemap>, Error>>()?;
+
+    let options = DocOptions {
+        doc_json_path: sub_args.get_one::<PathBuf>("doc-json").map(|path| cwd.join(path)),
+        path_remaps,
+        strict: sub_args.get_flag("strict"),
+        visibility: sub_args
+            .get_one::<String>("visibility")
+            .expect("Has a default")
+            .parse()?,
+        hide_underscored: sub_args.get_flag("hide-underscored"),
+        ..DocOptions::default()
+    };
+
+    Ok((library, options))
+}
+
+fn handle_generate(sub_args: &ArgMatches) -> Result<(), Error> {
+    let (library, mut options) = library_options(sub_args)?;
+    let cwd = env::current_dir()?;
+    let out = cwd.join(sub_args.get_one::<PathBuf>("out").expect("Required argument"));
+
+    options.inherited_members = sub_args.get_flag("inherited-members");
+    options.item_order = sub_args
+        .get_one::<String>("item-order")
+        .expect("Has a default")
+        .parse()?;
+    options.template_dir = sub_args.get_one::<PathBuf>("template-dir").map(|dir| cwd.join(dir));
+    options.page_granularity = sub_args
+        .get_one::<String>("page-granularity")
+        .expect("Has a default")
+        .parse()?;
+    options.index_chapters = sub_args.get_flag("index-chapters");
+
+    let written = generate(&library, &out, &options)?;
+    eprintln!("Wrote {} files to {}", written.len(), out.display());
+
+    Ok(())
+}
+
+fn handle_dump(sub_args: &ArgMatches) -> Result<(), Error> {
+    let (library, mut options) = library_options(sub_args)?;
+
+    options.inherited_members = sub_args.get_flag("inherited-members");
+    options.item_order = sub_args
+        .get_one::<String>("item-order")
+        .expect("Has a default")
+        .parse()?;
+
+    match sub_args.get_one::<PathBuf>("out") {
+        Some(out) => dump_docs(&library, &options, File::create(out)?)?,
+        None => dump_docs(&library, &options, io::stdout())?,
+    }
+
+    Ok(())
+}
+
+/// Print the coverage report, returning whether it meets the thresholds.
+fn handle_check(sub_args: &ArgMatches) -> Result<bool, Error> {
+    let (library, options) = library_options(sub_args)?;
+    let min_coverage = *sub_args.get_one::<f64>("min-coverage").expect("Has a default");
+    let min_file_coverage = sub_args.get_one::<f64>("min-file-coverage").copied();
+
+    let report = CoverageReport::build(&load_docs(&library, &options)?, &library);
+
+    if sub_args.get_flag("json") {
+        serde_json::to_writer_pretty(io::stdout(), &report)?;
+        println!();
+    } else {
+        let library_arg = sub_args.get_one::<PathBuf>("library").expect("Required argument");
+        for (path, undocumented) in report.undocumented() {
+            println!(
+                "{}:{}: undocumented {} `{}`",
+                library_arg.join(path).display(),
+                undocumented.line,
+                undocumented.kind,
+                undocumented.name
+            );
+        }
+        println!("{} of {} documented ({:.1}%)", report.documented, report.total, report.coverage);
+    }
+
+    let mut passed = true;
+    if report.coverage < min_coverage {
+        eprintln!("Coverage {:.1}% is below the minimum of {}%", report.coverage, min_coverage);
+        passed = false;
+    }
+    if let Some(min_file_coverage) = min_file_coverage {
+        for file in report.files_below(min_file_coverage) {
+            eprintln!(
+                "Coverage of {} is {:.1}%, below the minimum of {}%",
+                file.path.display(),
+                file.coverage,
+                min_file_coverage
+            );
+            passed = false;
+        }
+    }
+
+    Ok(passed)
+}
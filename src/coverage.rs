@@ -0,0 +1,281 @@
+//! Reports documented items, members, arguments and returns that are missing
+//! a description.
+
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    doctree::{DocItem, DocItemEnum, DocTree, Function, Global, MetaFile},
+    location::Range,
+};
+
+/// The kind of thing missing a description.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum CoverageKind {
+    Class,
+    Table,
+    TypeAlias,
+    Enum,
+    Global,
+    Function,
+    Field,
+    Argument,
+    Return,
+}
+
+impl Display for CoverageKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let kind = match self {
+            Self::Class => "class",
+            Self::Table => "table",
+            Self::TypeAlias => "type alias",
+            Self::Enum => "enum",
+            Self::Global => "global",
+            Self::Function => "function",
+            Self::Field => "field",
+            Self::Argument => "argument",
+            Self::Return => "return",
+        };
+
+        write!(f, "{}", kind)
+    }
+}
+
+/// Something that should be documented, but isn't.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Undocumented {
+    pub kind: CoverageKind,
+    /// The qualified name, such as `Class.method` or `Class.method(arg)`.
+    pub name: String,
+    /// The line in the file (one-based).
+    pub line: u64,
+}
+
+/// The documentation coverage of one file.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FileCoverage {
+    /// The file path, relative to the library.
+    pub path: PathBuf,
+    pub documented: usize,
+    pub total: usize,
+    /// The percentage of documented things, or 100 when there's nothing to
+    /// document.
+    pub coverage: f64,
+    pub undocumented: Vec<Undocumented>,
+}
+
+impl FileCoverage {
+    fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            documented: 0,
+            total: 0,
+            coverage: 100.0,
+            undocumented: Vec::new(),
+        }
+    }
+
+    fn add(&mut self, kind: CoverageKind, name: String, description: Option<&str>, range: &Range) {
+        self.total += 1;
+
+        if description.is_some_and(|description| !description.trim().is_empty()) {
+            self.documented += 1;
+        } else {
+            self.undocumented.push(Undocumented {
+                kind,
+                name,
+                line: range.start().line + 1,
+            });
+        }
+
+        self.coverage = percentage(self.documented, self.total);
+    }
+
+    fn add_item(&mut self, item: &DocItem) {
+        let kind = match &item.inner {
+            DocItemEnum::Class(_) => CoverageKind::Class,
+            DocItemEnum::Table(_) => CoverageKind::Table,
+            DocItemEnum::TypeAlias(_) => CoverageKind::TypeAlias,
+            DocItemEnum::Enum(_) => CoverageKind::Enum,
+            DocItemEnum::Global(Global::Function(_)) => CoverageKind::Function,
+            DocItemEnum::Global(Global::Primitive(_)) => CoverageKind::Global,
+        };
+
+        match &item.inner {
+            DocItemEnum::Global(Global::Function(function)) => {
+                let description = item.description.as_deref().or(function.description.as_deref());
+                self.add(kind, item.name.clone(), description, &item.range);
+                self.add_signature(&item.name, function);
+            },
+            _ => self.add(kind, item.name.clone(), item.description.as_deref(), &item.range),
+        }
+
+        match &item.inner {
+            DocItemEnum::Class(class) => {
+                for field in class.fields.iter() {
+                    let name = format!("{}.{}", item.name, field.name);
+                    self.add(CoverageKind::Field, name, field.description.as_deref(), &field.range);
+                }
+                for method in class.methods.iter() {
+                    self.add_function(&format!("{}.{}", item.name, method.name), &method.function);
+                }
+            },
+            DocItemEnum::Table(table) => {
+                for field in table.fields.values() {
+                    let name = format!("{}.{}", item.name, field.name);
+                    self.add(CoverageKind::Field, name, field.description.as_deref(), &field.range);
+                }
+                for function in table.functions.values() {
+                    self.add_function(&format!("{}.{}", item.name, function.name), &function.function);
+                }
+            },
+            DocItemEnum::Enum(lua_enum) => {
+                for member in lua_enum.fields.values() {
+                    let name = format!("{}.{}", item.name, member.name);
+                    self.add(CoverageKind::Field, name, member.description.as_deref(), &member.range);
+                }
+            },
+            DocItemEnum::TypeAlias(_) | DocItemEnum::Global(_) => {},
+        }
+    }
+
+    fn add_function(&mut self, name: &str, function: &Function) {
+        self.add(CoverageKind::Function, name.to_string(), function.description.as_deref(), &function.range);
+        self.add_signature(name, function);
+    }
+
+    /// Add the arguments and returns of a function. `self` needs no
+    /// description.
+    fn add_signature(&mut self, name: &str, function: &Function) {
+        for (index, arg) in function.arguments.iter().enumerate() {
+            let arg_name = arg.name.clone().unwrap_or_else(|| format!("#{}", index + 1));
+            if arg_name == "self" {
+                continue
+            }
+
+            let name = format!("{}({})", name, arg_name);
+            self.add(CoverageKind::Argument, name, arg.description.as_deref(), &function.range);
+        }
+
+        for (index, ret) in function.returns.iter().enumerate() {
+            let ret_name = ret.name.clone().unwrap_or_else(|| format!("#{}", index + 1));
+            let name = format!("{} -> {}", name, ret_name);
+            self.add(CoverageKind::Return, name, ret.description.as_deref(), &function.range);
+        }
+    }
+}
+
+/// The documentation coverage of a library.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CoverageReport {
+    pub documented: usize,
+    pub total: usize,
+    pub coverage: f64,
+    pub files: Vec<FileCoverage>,
+}
+
+impl CoverageReport {
+    /// Check every file in the tree. File paths are made relative to the
+    /// library path.
+    pub fn build(tree: &DocTree, library: &Path) -> Self {
+        let mut files = Vec::new();
+
+        tree.for_each(|file: &MetaFile| {
+            let path = file.uri.to_file_path().unwrap_or_else(|_| PathBuf::from(file.uri.file_name()));
+            let path = path.strip_prefix(library).map(Path::to_path_buf).unwrap_or(path);
+
+            let mut coverage = FileCoverage::new(path);
+            for item in file.items.values() {
+                coverage.add_item(item);
+            }
+
+            files.push(coverage);
+        });
+
+        let documented = files.iter().map(|file| file.documented).sum();
+        let total = files.iter().map(|file| file.total).sum();
+
+        Self {
+            documented,
+            total,
+            coverage: percentage(documented, total),
+            files,
+        }
+    }
+
+    /// Every undocumented thing with the path of its file.
+    pub fn undocumented(&self) -> impl Iterator<Item = (&Path, &Undocumented)> {
+        self.files
+            .iter()
+            .flat_map(|file| file.undocumented.iter().map(|undocumented| (file.path.as_path(), undocumented)))
+    }
+
+    /// The files with a coverage below the given percentage.
+    pub fn files_below(&self, threshold: f64) -> impl Iterator<Item = &FileCoverage> {
+        self.files.iter().filter(move |file| file.coverage < threshold)
+    }
+}
+
+fn percentage(documented: usize, total: usize) -> f64 {
+    if total == 0 {
+        return 100.0
+    }
+
+    documented as f64 * 100.0 / total as f64
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        doctree::{Argument, Class, Field, NamedFunction, Return},
+        location::FileUri,
+        lua_type::LuaType,
+        testing::{function, item, range},
+    };
+
+    #[test]
+    fn report_undocumented() {
+        let method = Function {
+            description: Some("Greets someone".to_string()),
+            range: range(4),
+            arguments: vec![
+                Argument { name: Some("self".to_string()), description: None, arg_type: LuaType::name("self") },
+                Argument { name: Some("name".to_string()), description: None, arg_type: LuaType::name("string") },
+            ],
+            returns: vec![Return { name: None, return_type: LuaType::name("string"), description: Some("The greeting".to_string()) }],
+            ..function("Greeter:greet(name)")
+        };
+        let mut file = MetaFile::new(FileUri::parse("file:///library/greeter.lua").unwrap());
+        file.add_item(DocItem {
+            range: range(1),
+            ..item("Greeter", DocItemEnum::Class(Class {
+                fields: vec![Field::new("greeting".to_string(), Some("  ".to_string()), LuaType::name("string"), range(2))],
+                methods: vec![NamedFunction::new("greet".to_string(), method)],
+                ..Class::default()
+            }))
+        });
+        let mut tree = DocTree::new();
+        tree.add_item(file);
+
+        let report = CoverageReport::build(&tree, Path::new("/library"));
+
+        let undocumented: Vec<(String, CoverageKind, &str, u64)> = report
+            .undocumented()
+            .map(|(path, u)| (path.display().to_string(), u.kind, u.name.as_str(), u.line))
+            .collect();
+        assert_eq!(undocumented, vec![
+            ("greeter.lua".to_string(), CoverageKind::Class, "Greeter", 2),
+            ("greeter.lua".to_string(), CoverageKind::Field, "Greeter.greeting", 3),
+            ("greeter.lua".to_string(), CoverageKind::Argument, "Greeter.greet(name)", 5),
+        ]);
+        assert_eq!((report.documented, report.total), (2, 5));
+        assert_eq!(report.coverage, 40.0);
+        assert_eq!(report.files_below(50.0).count(), 1);
+        assert_eq!(report.files_below(40.0).count(), 0);
+    }
+}
@@ -0,0 +1,209 @@
+//! Problems found while building the docs, collected across every file so
+//! they can be reported together.
+
+use std::fmt::Display;
+
+use itertools::Itertools;
+use log::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    errors::*,
+    location::{Location, Range},
+    workspace::SourceFile,
+};
+
+/// The most lines of source shown for a diagnostic.
+const MAX_SNIPPET_LINES: usize = 5;
+
+/// How bad a problem is.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// Something was skipped, but the rest of the item was documented.
+    Warning,
+    /// An item couldn't be documented.
+    Error,
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Warning => write!(f, "warning"),
+            Self::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// A problem with a definition.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Diagnostic {
+    pub(crate) severity: Severity,
+    pub(crate) message: String,
+    pub(crate) location: Location,
+    /// The source lines of the location.
+    pub(crate) snippet: String,
+}
+
+impl Diagnostic {
+    pub fn severity(&self) -> Severity {
+        self.severity
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// The file and range of the definition with the problem.
+    pub fn location(&self) -> &Location {
+        &self.location
+    }
+
+    /// The source lines of the location.
+    pub fn snippet(&self) -> &str {
+        &self.snippet
+    }
+
+    pub(crate) fn new(severity: Severity, source_file: &SourceFile, range: Range, message: impl Display) -> Self {
+        let (start, end) = range.bounds();
+        let snippet = source_file
+            .text
+            .lines()
+            .enumerate()
+            .skip(usize::try_from(start.line).unwrap_or(usize::MAX))
+            .take(usize::try_from(end.line.saturating_sub(start.line) + 1).unwrap_or(usize::MAX).min(MAX_SNIPPET_LINES))
+            .map(|(index, line)| format!("{:>4} | {}", index + 1, line))
+            .join("\n");
+
+        Self {
+            severity,
+            message: message.to_string(),
+            location: Location {
+                file: source_file.uri.clone(),
+                range,
+            },
+            snippet,
+        }
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let start = self.location.range.start();
+        let path = match self.location.file.to_file_path() {
+            Ok(path) => path.display().to_string(),
+            Err(_) => self.location.file.to_string(),
+        };
+
+        write!(f, "{}: {}\n  --> {}:{}:{}", self.severity, self.message, path, start.line + 1, start.character + 1)?;
+        if !self.snippet.is_empty() {
+            write!(f, "\n{}", self.snippet)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The problems found while building the docs.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Diagnostics(Vec<Diagnostic>);
+
+impl Diagnostics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, diagnostic: Diagnostic) {
+        self.0.push(diagnostic);
+    }
+
+    pub(crate) fn error(&mut self, source_file: &SourceFile, range: Range, message: impl Display) {
+        self.push(Diagnostic::new(Severity::Error, source_file, range, message));
+    }
+
+    pub(crate) fn warning(&mut self, source_file: &SourceFile, range: Range, message: impl Display) {
+        self.push(Diagnostic::new(Severity::Warning, source_file, range, message));
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.0.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn count(&self, severity: Severity) -> usize {
+        self.0.iter().filter(|diagnostic| diagnostic.severity == severity).count()
+    }
+
+    /// Fail with every diagnostic when there are errors, or any diagnostics in
+    /// strict mode. Otherwise the warnings are logged.
+    pub fn check(&self, strict: bool) -> Result<()> {
+        let errors = self.count(Severity::Error);
+        let warnings = self.count(Severity::Warning);
+
+        if errors == 0 && (warnings == 0 || !strict) {
+            for diagnostic in self.0.iter() {
+                warn!("{}", diagnostic);
+            }
+            return Ok(())
+        }
+
+        bail!(
+            "Unable to build the docs: {} errors and {} warnings{}\n\n{}",
+            errors,
+            warnings,
+            if strict { " in strict mode" } else { "" },
+            self.0.iter().join("\n\n")
+        )
+    }
+}
+
+impl<'a> IntoIterator for &'a Diagnostics {
+    type Item = &'a Diagnostic;
+
+    type IntoIter = std::slice::Iter<'a, Diagnostic>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::location::{FileUri, Position};
+
+    #[test]
+    fn check_diagnostics() -> Result<()> {
+        let source_file = SourceFile::new(
+            FileUri::parse("file:///library/song.lua")?,
+            "---@class Song\nlocal Song = {}\nSong.name = nil\n".to_string(),
+        );
+        let range = Range::new(Position::unpack(2_0000), Position::unpack(2_0004));
+
+        let mut diagnostics = Diagnostics::new();
+        assert!(diagnostics.check(true).is_ok());
+
+        diagnostics.warning(&source_file, range, "Unexpected setfield type Nil");
+        assert_eq!(
+            diagnostics.iter().next().unwrap().to_string(),
+            "warning: Unexpected setfield type Nil\n  --> /library/song.lua:3:1\n   3 | Song.name = nil"
+        );
+        assert!(diagnostics.check(false).is_ok());
+
+        let error = diagnostics.check(true).unwrap_err().to_string();
+        assert!(error.starts_with("Unable to build the docs: 0 errors and 1 warnings in strict mode\n\nwarning:"), "{}", error);
+
+        diagnostics.error(&source_file, Range::new(Position::unpack(0), Position::unpack(1_0005)), "Invalid class");
+        let error = diagnostics.check(false).unwrap_err().to_string();
+        assert!(error.contains("error: Invalid class\n  --> /library/song.lua:1:1\n   1 | ---@class Song\n   2 | local Song = {}"), "{}", error);
+
+        Ok(())
+    }
+}
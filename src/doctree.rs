@@ -1,37 +1,60 @@
-use std::collections::HashMap;
+use std::cmp::Ordering;
+use std::path::{Path, PathBuf};
 
 use crate::{
+    diagnostics::Diagnostics,
     errors::*,
     json::{
         self, ArgType, DefineType, Definition, DefinitionType, Extends, ExtendsType, FieldType,
     },
     location::{FileUri, Range},
-    passes::{merge_class_tables, parse_items, parse_set_fields, parse_table_fields},
+    lua_type::LuaType,
+    passes::{
+        inherit_members, merge_class_tables, parse_items, parse_overloads, parse_set_fields,
+        parse_annotations, parse_table_fields, report_unknown_types,
+    },
     workspace::Workspace,
 };
+use indexmap::IndexMap;
 use itertools::Itertools;
 use log::debug;
 use serde::{Deserialize, Serialize};
 
+/// Build the docs for every file loaded in the workspace. Fails with every
+/// problem found when any definition couldn't be documented, and logs any
+/// warnings.
 pub fn build_docs(workspace: Workspace) -> Result<DocTree> {
+    let (tree, diagnostics) = build_docs_with_diagnostics(workspace);
+    diagnostics.check(false)?;
+
+    Ok(tree)
+}
+
+/// Build the docs for every file loaded in the workspace, along with the
+/// problems found in the definitions.
+pub fn build_docs_with_diagnostics(workspace: Workspace) -> (DocTree, Diagnostics) {
     debug!("building docs");
 
     let mut meta_files: Vec<MetaFile> = Vec::new();
+    let mut diagnostics = Diagnostics::new();
 
     for source_file in workspace.into_iter() {
         let mut meta_file = MetaFile::new(source_file.uri.clone());
 
-        parse_items(&mut meta_file, source_file)?;
-        parse_set_fields(&mut meta_file, source_file)?;
-        parse_table_fields(&mut meta_file, source_file)?;
-        merge_class_tables(&mut meta_file, source_file)?;
+        report_unknown_types(source_file, &mut diagnostics);
+        parse_items(&mut meta_file, source_file, &mut diagnostics);
+        parse_set_fields(&mut meta_file, source_file, &mut diagnostics);
+        parse_table_fields(&mut meta_file, source_file, &mut diagnostics);
+        parse_overloads(&mut meta_file, source_file, &mut diagnostics);
+        parse_annotations(&mut meta_file, source_file);
+        merge_class_tables(&mut meta_file, source_file, &mut diagnostics);
 
         meta_files.push(meta_file);
     }
 
     let tree = build_tree(&workspace.root, meta_files);
 
-    Ok(tree)
+    (tree, diagnostics)
 }
 
 fn build_tree(root: &FileUri, meta_files: Vec<MetaFile>) -> DocTree {
@@ -68,20 +91,158 @@ fn build_tree(root: &FileUri, meta_files: Vec<MetaFile>) -> DocTree {
 pub struct DocTree(Vec<MetaFile>);
 
 impl DocTree {
-    pub fn new() -> Self {
+    pub(crate) fn new() -> Self {
         Self::default()
     }
 
-    pub fn for_each_mut<F>(&mut self, mut func: F)
+    /// The top level files. Files in subdirectories are children of the file
+    /// with the same name as the directory.
+    pub fn files(&self) -> &[MetaFile] {
+        &self.0
+    }
+
+    /// Call the function with every file, parents before their children.
+    pub fn for_each<F>(&self, mut func: F)
+    where
+        F: FnMut(&MetaFile),
+    {
+        for_each(&mut func, &self.0);
+    }
+
+    pub(crate) fn for_each_mut<F>(&mut self, mut func: F)
     where
         F: FnMut(&mut MetaFile),
     {
         for_each_mut(&mut func, &mut self.0);
     }
 
-    pub fn add_item(&mut self, item: MetaFile) {
+    pub(crate) fn add_item(&mut self, item: MetaFile) {
         self.0.push(item)
     }
+
+    /// Add the members each class inherits from its ancestors.
+    pub fn add_inherited_members(&mut self) {
+        inherit_members(self)
+    }
+
+    /// Find an item by name, or one of its members by the item name followed
+    /// by `.` or `:` and the member name.
+    pub fn find(&self, path: &str) -> Option<ItemRef<'_>> {
+        if let Some(item) = find_item(&self.0, path) {
+            return Some(ItemRef::Item(item))
+        }
+
+        let (item, member) = path.rsplit_once([':', '.'])?;
+        find_item(&self.0, item)?.member(member)
+    }
+
+    /// Sort the items and members of every file.
+    pub fn sort(&mut self, order: ItemOrder) {
+        self.for_each_mut(|file| file.sort(order))
+    }
+
+    /// Remove the fields, methods and table functions the filter hides, and
+    /// those with a name starting with `_` when `hide_underscored` is set.
+    pub fn filter_members(&mut self, visibility: VisibilityFilter, hide_underscored: bool) {
+        let shown = |name: &str, member: Visibility| {
+            visibility.includes(member) && !(hide_underscored && name.starts_with('_'))
+        };
+
+        self.for_each_mut(|file| file.filter_members(&shown))
+    }
+}
+
+/// The order items and members are listed in on a page.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[non_exhaustive]
+pub enum ItemOrder {
+    /// The order they're declared in the source file.
+    #[default]
+    Source,
+    Alphabetical,
+}
+
+impl ItemOrder {
+    fn compare(self, (a_name, a_range): (&str, &Range), (b_name, b_range): (&str, &Range)) -> Ordering {
+        match self {
+            Self::Source => a_range.cmp(b_range).then_with(|| a_name.cmp(b_name)),
+            Self::Alphabetical => a_name.cmp(b_name).then_with(|| a_range.cmp(b_range)),
+        }
+    }
+}
+
+impl std::str::FromStr for ItemOrder {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "source" => Ok(Self::Source),
+            "alphabetical" => Ok(Self::Alphabetical),
+            _ => bail!("unknown item order {:?}, expected \"source\" or \"alphabetical\"", s),
+        }
+    }
+}
+
+/// Which members are documented, by their visibility.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[non_exhaustive]
+pub enum VisibilityFilter {
+    #[default]
+    Public,
+    /// Public and protected members.
+    Protected,
+    /// Every member, including private and package ones.
+    All,
+}
+
+impl VisibilityFilter {
+    fn includes(self, visibility: Visibility) -> bool {
+        match self {
+            Self::Public => visibility == Visibility::Public,
+            Self::Protected => visibility <= Visibility::Protected,
+            Self::All => true,
+        }
+    }
+}
+
+impl std::str::FromStr for VisibilityFilter {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "public" => Ok(Self::Public),
+            "protected" => Ok(Self::Protected),
+            "all" => Ok(Self::All),
+            _ => bail!("unknown visibility {:?}, expected \"public\", \"protected\" or \"all\"", s),
+        }
+    }
+}
+
+/// How the docs are split into pages.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[non_exhaustive]
+pub enum PageGranularity {
+    /// A page for each definition file.
+    #[default]
+    File,
+    /// A page for each definition file, with a sub-page for each class, table
+    /// and enum in the file.
+    Item,
+}
+
+impl std::str::FromStr for PageGranularity {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "file" => Ok(Self::File),
+            "item" => Ok(Self::Item),
+            _ => bail!("unknown page granularity {:?}, expected \"file\" or \"item\"", s),
+        }
+    }
 }
 
 impl IntoIterator for DocTree {
@@ -94,7 +255,25 @@ impl IntoIterator for DocTree {
     }
 }
 
-pub fn for_each_mut<'a, F, I>(func: &mut F, items: I)
+pub(crate) fn for_each<'a, F, I>(func: &mut F, items: I)
+where
+    F: FnMut(&MetaFile),
+    I: IntoIterator<Item = &'a MetaFile>,
+{
+    for item in items {
+        func(item);
+
+        for_each(func, &item.children);
+    }
+}
+
+fn find_item<'a>(files: &'a [MetaFile], name: &str) -> Option<&'a DocItem> {
+    files.iter().find_map(|file| {
+        file.items.get(name).or_else(|| find_item(&file.children, name))
+    })
+}
+
+pub(crate) fn for_each_mut<'a, F, I>(func: &mut F, items: I)
 where
     F: FnMut(&mut MetaFile),
     I: IntoIterator<Item = &'a mut MetaFile>,
@@ -108,36 +287,129 @@ where
 
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub struct MetaFile {
-    pub uri: FileUri,
-    pub children: Vec<MetaFile>,
-    pub items: HashMap<String, DocItem>,
+    pub(crate) uri: FileUri,
+    pub(crate) children: Vec<MetaFile>,
+    pub(crate) items: IndexMap<String, DocItem>,
 }
 
 impl MetaFile {
-    pub fn new(uri: FileUri) -> Self {
+    pub(crate) fn new(uri: FileUri) -> Self {
         Self {
             uri,
             children: Vec::new(),
-            items: HashMap::new(),
+            items: IndexMap::new(),
         }
     }
 
-    pub fn add_item(&mut self, item: DocItem) {
+    /// The URI of the definition file.
+    pub fn uri(&self) -> &FileUri {
+        &self.uri
+    }
+
+    /// The files in the directory with the same name as this file.
+    pub fn children(&self) -> &[MetaFile] {
+        &self.children
+    }
+
+    /// The items in this file, in page order.
+    pub fn items(&self) -> impl Iterator<Item = &DocItem> {
+        self.items.values()
+    }
+
+    pub fn item(&self, name: &str) -> Option<&DocItem> {
+        self.items.get(name)
+    }
+
+    /// Add an item, replacing any item with the same name. A global function
+    /// declared again with the same name is kept as an overload.
+    pub(crate) fn add_item(&mut self, item: DocItem) {
+        if let Some(existing) = self.items.get_mut(&item.name) {
+            if let (
+                DocItemEnum::Global(Global::Function(existing)),
+                DocItemEnum::Global(Global::Function(overload)),
+            ) = (&mut existing.inner, &item.inner) {
+                existing.add_overload(overload.clone());
+                return
+            }
+        }
+
         self.items.insert(item.name.clone(), item);
     }
+
+    /// Sort the items in this file and the members of each item.
+    pub(crate) fn sort(&mut self, order: ItemOrder) {
+        self.items.sort_by(|_, a, _, b| order.compare((&a.name, &a.range), (&b.name, &b.range)));
+
+        for item in self.items.values_mut() {
+            match &mut item.inner {
+                DocItemEnum::Class(class) => class.sort(order),
+                DocItemEnum::Table(table) => table.sort(order),
+                DocItemEnum::Enum(lua_enum) => lua_enum.sort(order),
+                DocItemEnum::TypeAlias(_) | DocItemEnum::Global(_) => {},
+            }
+        }
+    }
+
+    /// Keep the members of classes and tables that are shown.
+    fn filter_members(&mut self, shown: &impl Fn(&str, Visibility) -> bool) {
+        for item in self.items.values_mut() {
+            match &mut item.inner {
+                DocItemEnum::Class(class) => {
+                    class.fields.retain(|field| shown(&field.name, field.visibility));
+                    class.methods.retain(|method| shown(&method.name, method.visibility));
+                },
+                DocItemEnum::Table(table) => {
+                    table.fields.retain(|_, field| shown(&field.name, field.visibility));
+                    table.functions.retain(|_, function| shown(&function.name, function.visibility));
+                },
+                _ => {},
+            }
+        }
+    }
+
+    /// The path of the chapter for this file, relative to the definitions path.
+    pub fn page_path(&self, base: &Path) -> Result<PathBuf> {
+        Ok(self.uri.to_file_path()?
+            .strip_prefix(base)?
+            .with_extension("md"))
+    }
+
+    /// The items rendered on their own pages.
+    pub fn page_items(&self, granularity: PageGranularity) -> impl Iterator<Item = &DocItem> {
+        self.items
+            .values()
+            .filter(move |item| granularity == PageGranularity::Item && item.has_own_page())
+    }
+
+    /// The path of the page for an item, in the directory named after the
+    /// file. The `.item.md` extension keeps it apart from the pages of child
+    /// modules, which can't be required with a `.` in their file name.
+    /// Characters that aren't safe in file names are replaced with `_`.
+    pub fn item_page_path(&self, base: &Path, item: &DocItem) -> Result<PathBuf> {
+        let name: String = item.name
+            .chars()
+            .map(|c| if c.is_alphanumeric() || matches!(c, '_' | '-' | '.') { c } else { '_' })
+            .collect();
+
+        Ok(self.page_path(base)?
+            .with_extension("")
+            .join(format!("{}.item.md", name)))
+    }
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub struct DocItem {
-    pub name: String,
-    pub description: Option<String>,
-    pub range: Range,
+    pub(crate) name: String,
+    pub(crate) description: Option<String>,
+    pub(crate) range: Range,
+    pub(crate) deprecated: Option<Deprecation>,
     #[serde(flatten)]
-    pub inner: DocItemEnum,
+    pub(crate) inner: DocItemEnum,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 #[serde(tag = "kind")]
+#[non_exhaustive]
 pub enum DocItemEnum {
     Class(Class),
     Table(Table),
@@ -146,8 +418,110 @@ pub enum DocItemEnum {
     Global(Global),
 }
 
+/// A documented item, or a member of one along with its item.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum ItemRef<'a> {
+    Item(&'a DocItem),
+    Function(&'a DocItem, &'a NamedFunction),
+    Field(&'a DocItem, &'a Field),
+}
+
+impl ItemRef<'_> {
+    /// The item name, or the item and member names joined with a `.`.
+    pub fn path(&self) -> String {
+        match self {
+            Self::Item(item) => item.name.clone(),
+            Self::Function(item, function) => format!("{}.{}", item.name, function.name),
+            Self::Field(item, field) => format!("{}.{}", item.name, field.name),
+        }
+    }
+}
+
 impl DocItem {
-    pub fn parse(definition: &Definition) -> Result<Option<Self>> {
+    /// Whether the item can have its own page.
+    fn has_own_page(&self) -> bool {
+        matches!(self.inner, DocItemEnum::Class(_) | DocItemEnum::Table(_) | DocItemEnum::Enum(_))
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    /// Where the item is declared.
+    pub fn range(&self) -> Range {
+        self.range
+    }
+
+    pub fn deprecated(&self) -> Option<&Deprecation> {
+        self.deprecated.as_ref()
+    }
+
+    pub fn kind(&self) -> &DocItemEnum {
+        &self.inner
+    }
+
+    pub fn as_class(&self) -> Option<&Class> {
+        match &self.inner {
+            DocItemEnum::Class(class) => Some(class),
+            _ => None,
+        }
+    }
+
+    pub fn as_table(&self) -> Option<&Table> {
+        match &self.inner {
+            DocItemEnum::Table(table) => Some(table),
+            _ => None,
+        }
+    }
+
+    pub fn as_type_alias(&self) -> Option<&TypeAlias> {
+        match &self.inner {
+            DocItemEnum::TypeAlias(alias) => Some(alias),
+            _ => None,
+        }
+    }
+
+    pub fn as_enum(&self) -> Option<&Enum> {
+        match &self.inner {
+            DocItemEnum::Enum(lua_enum) => Some(lua_enum),
+            _ => None,
+        }
+    }
+
+    pub fn as_global(&self) -> Option<&Global> {
+        match &self.inner {
+            DocItemEnum::Global(global) => Some(global),
+            _ => None,
+        }
+    }
+
+    /// Find a method, function or field of a class or table.
+    pub fn member(&self, name: &str) -> Option<ItemRef<'_>> {
+        match &self.inner {
+            DocItemEnum::Class(class) => class.methods
+                .iter()
+                .find(|method| method.name == name)
+                .map(|method| ItemRef::Function(self, method))
+                .or_else(|| {
+                    class.fields
+                        .iter()
+                        .find(|field| field.name == name)
+                        .map(|field| ItemRef::Field(self, field))
+                }),
+            DocItemEnum::Table(table) => table.functions
+                .get(name)
+                .map(|function| ItemRef::Function(self, function))
+                .or_else(|| table.fields.get(name).map(|field| ItemRef::Field(self, field))),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn parse(definition: &Definition) -> Result<Option<Self>> {
         let inner = match definition.defines.head.define_type {
             DefineType::DocAlias => Some(DocItemEnum::TypeAlias(TypeAlias::parse(definition)?)),
             DefineType::DocClass => Some(DocItemEnum::Class(Class::parse(definition)?)),
@@ -165,6 +539,8 @@ impl DocItem {
                         bail!("unexpected doc extend for setglobal")
                     }
                     ExtendsType::Table => Some(DocItemEnum::Table(Table::parse(definition)?)),
+                    // Reported when building the docs
+                    ExtendsType::Other(_) => None,
                     _ => Some(DocItemEnum::Global(Global::parse(definition)?)),
                 }
             }
@@ -172,10 +548,15 @@ impl DocItem {
             _ => None,
         };
 
+        let deprecated = definition.defines.head.extends
+            .first()
+            .and_then(|extends| Deprecation::from_flag(extends.deprecated));
+
         Ok(inner.map(|inner| DocItem {
             name: definition.name.clone(),
             description: definition.rawdesc.clone(),
-            range: definition.defines.head.location.range.clone(),
+            range: definition.defines.head.location.range,
+            deprecated,
             inner,
         }))
     }
@@ -183,15 +564,56 @@ impl DocItem {
 
 #[derive(Clone, Debug, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize, Default)]
 pub struct Class {
-    pub fields: Vec<Field>,
-    pub methods: Vec<NamedFunction>,
+    /// The types this class inherits from.
+    pub(crate) parents: Vec<LuaType>,
+    pub(crate) fields: Vec<Field>,
+    pub(crate) methods: Vec<NamedFunction>,
+    /// Members inherited from ancestor classes, nearest ancestor first. Only
+    /// populated when inherited members are enabled.
+    pub(crate) inherited: Vec<InheritedMembers>,
+}
+
+/// The members a class inherits from one of its ancestors.
+#[derive(Clone, Debug, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct InheritedMembers {
+    /// The name of the class declaring these members.
+    pub(crate) class: String,
+    pub(crate) fields: Vec<Field>,
+    pub(crate) methods: Vec<NamedFunction>,
 }
 
 impl Class {
-    pub fn parse(definition: &Definition) -> Result<Self> {
+    /// The types this class inherits from.
+    pub fn parents(&self) -> &[LuaType] {
+        &self.parents
+    }
+
+    pub fn fields(&self) -> &[Field] {
+        &self.fields
+    }
+
+    pub fn methods(&self) -> &[NamedFunction] {
+        &self.methods
+    }
+
+    /// Members inherited from ancestor classes, nearest ancestor first.
+    pub fn inherited(&self) -> &[InheritedMembers] {
+        &self.inherited
+    }
+
+    pub(crate) fn parse(definition: &Definition) -> Result<Self> {
         ensure!(definition.definition_type == DefinitionType::Type);
         ensure!(definition.defines.head.define_type == DefineType::DocClass);
 
+        let parents: Vec<LuaType> = definition
+            .defines
+            .head
+            .extends
+            .iter()
+            .filter(|e| e.extends_type == ExtendsType::DocExtendsName)
+            .map(LuaType::from_extends)
+            .collect();
+
         let fields: Vec<Field> = definition
             .fields
             .iter()
@@ -199,7 +621,7 @@ impl Class {
                 (f.field_type == FieldType::DocField || f.field_type == FieldType::SetField)
                     && f.extends.extends_type != ExtendsType::Function
             })
-            .map(|f| Field::parse(f))
+            .map(Field::parse)
             .collect::<Result<Vec<Field>>>()?;
 
         let functions: Vec<NamedFunction> = definition
@@ -208,39 +630,82 @@ impl Class {
             .filter(|f| 
                 f.field_type == FieldType::SetField
                     && f.extends.extends_type == ExtendsType::Function
-            ).map(|field| -> Result<NamedFunction> {
-                Ok(NamedFunction {
-                    name: field.name.clone(),
-                    function: Function::parse(&field.extends)?,
-                })
-            })
+            ).map(NamedFunction::from_field)
             .collect::<Result<Vec<NamedFunction>>>()?;
 
         let methods: Vec<NamedFunction> = definition
             .fields
             .iter()
             .filter(|f| f.field_type == FieldType::SetMethod)
-            .map(|f| NamedFunction::parse(f))
+            .map(NamedFunction::parse)
             .collect::<Result<Vec<NamedFunction>>>()?;
 
         let class = Self {
+            parents,
             fields,
-            methods: functions.into_iter().merge(methods).collect(),
+            methods: merge_overloads(functions.into_iter().merge(methods)),
+            inherited: Vec::new(),
         };
 
         Ok(class)
     }
+
+    pub(crate) fn sort(&mut self, order: ItemOrder) {
+        sort_fields(&mut self.fields, order);
+        sort_functions(&mut self.methods, order);
+
+        for inherited in self.inherited.iter_mut() {
+            sort_fields(&mut inherited.fields, order);
+            sort_functions(&mut inherited.methods, order);
+        }
+    }
+}
+
+impl InheritedMembers {
+    /// The name of the class declaring these members.
+    pub fn class(&self) -> &str {
+        &self.class
+    }
+
+    pub fn fields(&self) -> &[Field] {
+        &self.fields
+    }
+
+    pub fn methods(&self) -> &[NamedFunction] {
+        &self.methods
+    }
+}
+
+fn sort_fields(fields: &mut [Field], order: ItemOrder) {
+    fields.sort_by(|a, b| order.compare((&a.name, &a.range), (&b.name, &b.range)));
+}
+
+fn sort_functions(functions: &mut [NamedFunction], order: ItemOrder) {
+    functions.sort_by(|a, b| order.compare((&a.name, &a.function.range), (&b.name, &b.function.range)));
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, Default)]
 pub struct Table {
-    pub view: String,
-    pub fields: HashMap<String, Field>,
-    pub functions: HashMap<String, NamedFunction>,
+    pub(crate) view: String,
+    pub(crate) fields: IndexMap<String, Field>,
+    pub(crate) functions: IndexMap<String, NamedFunction>,
 }
 
 impl Table {
-    pub fn parse(definition: &Definition) -> Result<Self> {
+    /// The LuaLS view of the table.
+    pub fn view(&self) -> &str {
+        &self.view
+    }
+
+    pub fn fields(&self) -> impl Iterator<Item = &Field> {
+        self.fields.values()
+    }
+
+    pub fn functions(&self) -> impl Iterator<Item = &NamedFunction> {
+        self.functions.values()
+    }
+
+    pub(crate) fn parse(definition: &Definition) -> Result<Self> {
         ensure!(definition.defines.head.define_type == DefineType::SetGlobal);
         let extends = definition
             .defines
@@ -252,28 +717,46 @@ impl Table {
 
         Ok(Table {
             view: extends.view.clone(),
-            fields: HashMap::new(),
-            functions: HashMap::new(),
+            fields: IndexMap::new(),
+            functions: IndexMap::new(),
         })
     }
 
-    pub fn add_field(&mut self, field: Field) {
+    pub(crate) fn add_field(&mut self, field: Field) {
         self.fields.insert(field.name.clone(), field);
     }
 
-    pub fn add_function(&mut self, function: NamedFunction) {
-        self.functions.insert(function.name.clone(), function);
+    /// Add a function. A function declared again with the same name is kept
+    /// as an overload.
+    pub(crate) fn add_function(&mut self, function: NamedFunction) {
+        match self.functions.get_mut(&function.name) {
+            Some(existing) => existing.function.add_overload(function.function),
+            None => {
+                self.functions.insert(function.name.clone(), function);
+            },
+        }
+    }
+
+    pub(crate) fn sort(&mut self, order: ItemOrder) {
+        self.fields.sort_by(|_, a, _, b| order.compare((&a.name, &a.range), (&b.name, &b.range)));
+        self.functions.sort_by(|_, a, _, b| {
+            order.compare((&a.name, &a.function.range), (&b.name, &b.function.range))
+        });
     }
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct TypeAlias {
     #[serde(rename = "type")]
-    pub aliased_type: String,
+    pub(crate) aliased_type: LuaType,
 }
 
 impl TypeAlias {
-    pub fn parse(definition: &Definition) -> Result<Self> {
+    pub fn aliased_type(&self) -> &LuaType {
+        &self.aliased_type
+    }
+
+    pub(crate) fn parse(definition: &Definition) -> Result<Self> {
         let define = &definition.defines.head;
         ensure!(define.define_type == DefineType::DocAlias);
         let extends = define
@@ -283,31 +766,142 @@ impl TypeAlias {
         ensure!(extends.extends_type == ExtendsType::DocType);
 
         Ok(Self {
-            aliased_type: extends.view.clone(),
+            aliased_type: LuaType::from_extends(extends),
         })
     }
 }
 
-#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, Default)]
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub struct Enum {
-    pub fields: HashMap<String,Field>,
+    /// The type of the enum, inferred from the member values.
+    #[serde(rename = "type")]
+    pub(crate) enum_type: LuaType,
+    pub(crate) fields: IndexMap<String, EnumMember>,
+}
+
+impl Default for Enum {
+    fn default() -> Self {
+        Self {
+            enum_type: LuaType::name("unknown"),
+            fields: IndexMap::new(),
+        }
+    }
 }
 
 impl Enum {
-    pub fn parse(definition: &Definition) -> Result<Self> {
+    /// The type of the enum, inferred from the member values.
+    pub fn enum_type(&self) -> &LuaType {
+        &self.enum_type
+    }
+
+    pub fn members(&self) -> impl Iterator<Item = &EnumMember> {
+        self.fields.values()
+    }
+
+    pub(crate) fn parse(definition: &Definition) -> Result<Self> {
         let define = &definition.defines.head;
         ensure!(define.define_type == DefineType::DocEnum);
 
         Ok(Self::default())
     }
 
-    pub fn add_field(&mut self, field: Field) {
+    pub(crate) fn add_field(&mut self, field: EnumMember) {
         self.fields.insert(field.name.clone(), field);
+        self.enum_type = self.infer_type();
+    }
+
+    pub(crate) fn sort(&mut self, order: ItemOrder) {
+        self.fields.sort_by(|_, a, _, b| order.compare((&a.name, &a.range), (&b.name, &b.range)));
+    }
+
+    /// Infer the enum type from the types of its members.
+    fn infer_type(&self) -> LuaType {
+        let mut types: Vec<LuaType> = self.fields
+            .values()
+            .map(|field| field.lua_type.clone())
+            .unique()
+            .collect();
+        types.sort();
+
+        match types.len() {
+            0 => LuaType::name("unknown"),
+            1 => types.remove(0),
+            _ => LuaType::Union { types },
+        }
     }
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
-#[serde(tag = "kind")]
+pub struct EnumMember {
+    pub(crate) name: String,
+    pub(crate) description: Option<String>,
+    /// The member value as written in the source.
+    pub(crate) value: Option<String>,
+    #[serde(rename = "type")]
+    pub(crate) lua_type: LuaType,
+    pub(crate) range: Range,
+}
+
+impl EnumMember {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    /// The member value as written in the source.
+    pub fn value(&self) -> Option<&str> {
+        self.value.as_deref()
+    }
+
+    pub fn lua_type(&self) -> &LuaType {
+        &self.lua_type
+    }
+
+    pub fn range(&self) -> Range {
+        self.range
+    }
+
+    pub(crate) fn new(name: String, description: Option<String>, value: Option<String>, range: Range) -> Self {
+        let lua_type = value
+            .as_deref()
+            .map(literal_type)
+            .unwrap_or_else(|| LuaType::name("unknown"));
+
+        Self {
+            name,
+            description,
+            value,
+            lua_type,
+            range,
+        }
+    }
+}
+
+/// The type of a Lua literal expression.
+fn literal_type(value: &str) -> LuaType {
+    let name = if value.parse::<i64>().is_ok()
+        || value.strip_prefix("0x").is_some_and(|hex| i64::from_str_radix(hex, 16).is_ok())
+    {
+        "integer"
+    } else if value.parse::<f64>().is_ok() {
+        "number"
+    } else if value.starts_with(['"', '\'', '[']) {
+        "string"
+    } else if value == "true" || value == "false" {
+        "boolean"
+    } else {
+        "unknown"
+    };
+
+    LuaType::name(name)
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(tag = "global")]
+#[non_exhaustive]
 pub enum Global {
     Primitive(PrimitiveGlobal),
     Function(Function),
@@ -316,11 +910,17 @@ pub enum Global {
 #[derive(Clone, Debug, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct PrimitiveGlobal {
     #[serde(rename = "type")]
-    primitive_type: String,
+    primitive_type: LuaType,
+}
+
+impl PrimitiveGlobal {
+    pub fn primitive_type(&self) -> &LuaType {
+        &self.primitive_type
+    }
 }
 
 impl Global {
-    pub fn parse(definition: &Definition) -> Result<Self> {
+    pub(crate) fn parse(definition: &Definition) -> Result<Self> {
         ensure!(definition.defines.head.define_type == DefineType::SetGlobal);
         let extends = definition
             .defines
@@ -340,8 +940,20 @@ impl Global {
             | ExtendsType::Integer
             | ExtendsType::Nil
             | ExtendsType::Number
-            | ExtendsType::String => Global::Primitive(PrimitiveGlobal { primitive_type: extends.view.clone() }),
-            ExtendsType::Function => Global::Function(Function::parse(extends)?),
+            | ExtendsType::String => Global::Primitive(PrimitiveGlobal { primitive_type: LuaType::from_extends(extends) }),
+            ExtendsType::Function => {
+                let mut function = Function::parse(extends)?;
+
+                for define in definition.defines.tail.iter() {
+                    for extends in define.extends.iter() {
+                        if extends.extends_type == ExtendsType::Function {
+                            function.add_overload(Function::parse(extends)?);
+                        }
+                    }
+                }
+
+                Global::Function(function)
+            },
             _ => bail!("unexpected extends type {:?}", extends.extends_type),
         })
     }
@@ -349,46 +961,128 @@ impl Global {
 
 #[derive(Clone, Debug, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct Field {
-    pub name: String,
-    pub description: Option<String>,
+    pub(crate) name: String,
+    pub(crate) description: Option<String>,
     #[serde(rename = "type")]
-    pub lua_type: String,
+    pub(crate) lua_type: LuaType,
+    pub(crate) range: Range,
+    pub(crate) deprecated: Option<Deprecation>,
+    pub(crate) visibility: Visibility,
 }
 
 impl Field {
-    pub fn parse(field: &json::Field) -> Result<Self> {
+    pub(crate) fn new(name: String, description: Option<String>, lua_type: LuaType, range: Range) -> Self {
+        Self {
+            name,
+            description,
+            lua_type,
+            range,
+            deprecated: None,
+            visibility: Visibility::Public,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    pub fn lua_type(&self) -> &LuaType {
+        &self.lua_type
+    }
+
+    pub fn range(&self) -> Range {
+        self.range
+    }
+
+    pub fn deprecated(&self) -> Option<&Deprecation> {
+        self.deprecated.as_ref()
+    }
+
+    pub fn visibility(&self) -> Visibility {
+        self.visibility
+    }
+
+    pub(crate) fn parse(field: &json::Field) -> Result<Self> {
         ensure!(field.field_type == FieldType::DocField || field.field_type == FieldType::SetField);
 
         Ok(Field {
             name: field.name.clone(),
             description: field.rawdesc.clone(),
-            lua_type: field.extends.view.clone(),
+            lua_type: LuaType::from_extends(&field.extends),
+            range: field.location.range,
+            deprecated: Deprecation::from_flag(field.deprecated),
+            visibility: Visibility::parse(field.visible.as_ref()),
         })
     }
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct Function {
-    pub description: Option<String>,
-    pub view: String,
-    pub arguments: Vec<Argument>,
-    pub returns: Vec<Return>,
+    pub(crate) description: Option<String>,
+    pub(crate) view: String,
+    pub(crate) range: Range,
+    pub(crate) arguments: Vec<Argument>,
+    pub(crate) returns: Vec<Return>,
+    /// Other signatures for the same function.
+    pub(crate) overloads: Vec<Function>,
+    pub(crate) is_async: bool,
+    /// Whether the caller must use the returned values.
+    pub(crate) nodiscard: bool,
 }
 
 impl Function {
-    pub fn parse(extends: &Extends) -> Result<Self> {
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    /// The LuaLS view of the signature.
+    pub fn view(&self) -> &str {
+        &self.view
+    }
+
+    pub fn range(&self) -> Range {
+        self.range
+    }
+
+    pub fn arguments(&self) -> &[Argument] {
+        &self.arguments
+    }
+
+    pub fn returns(&self) -> &[Return] {
+        &self.returns
+    }
+
+    /// Other signatures for the same function.
+    pub fn overloads(&self) -> &[Function] {
+        &self.overloads
+    }
+
+    pub fn is_async(&self) -> bool {
+        self.is_async
+    }
+
+    /// Whether the caller must use the returned values.
+    pub fn nodiscard(&self) -> bool {
+        self.nodiscard
+    }
+
+    pub(crate) fn parse(extends: &Extends) -> Result<Self> {
         ensure!(extends.extends_type == ExtendsType::Function);
 
         let arguments = extends
             .args
             .iter()
-            .map(|arg| Argument::parse(arg))
+            .map(Argument::parse)
             .collect::<Result<Vec<Argument>>>()?;
 
         let returns = extends
             .returns
             .iter()
-            .map(|ret| Return::parse(ret))
+            .map(Return::parse)
             .collect::<Result<Vec<Return>>>()?;
 
         let mut view = extends.view.clone();
@@ -399,46 +1093,232 @@ impl Function {
         Ok(Self {
             description: extends.rawdesc.clone(),
             view,
+            range: extends.range,
             arguments,
             returns,
+            overloads: Vec::new(),
+            is_async: extends.is_async.unwrap_or(false),
+            nodiscard: false,
         })
     }
+
+    /// Build an overload of this function from a `fun(...)` signature, such as
+    /// one declared with `---@overload`.
+    pub(crate) fn overload(&self, signature: &LuaType) -> Option<Self> {
+        let LuaType::Function { params, returns } = signature else {
+            return None
+        };
+
+        let arguments: Vec<Argument> = params
+            .iter()
+            .map(|param| {
+                let arg_type = param.param_type.clone().unwrap_or_else(|| LuaType::name("any"));
+
+                Argument {
+                    name: param.name.clone(),
+                    description: None,
+                    arg_type: if param.optional {
+                        LuaType::Optional { inner: Box::new(arg_type) }
+                    } else {
+                        arg_type
+                    },
+                }
+            })
+            .collect();
+
+        let returns: Vec<Return> = returns
+            .iter()
+            .map(|return_type| Return {
+                name: None,
+                return_type: return_type.clone(),
+                description: None,
+            })
+            .collect();
+
+        // Match the LuaLS view of the function this overloads
+        let name = self.view.split('(').next().unwrap_or_default();
+        let params_view = LuaType::Function { params: params.clone(), returns: Vec::new() }.to_string();
+        let mut view = format!("{}{}", name, params_view.trim_start_matches("fun"));
+        for (i, ret) in returns.iter().enumerate() {
+            match i {
+                0 => view.push_str(&format!("\n  -> {}", ret.return_type)),
+                _ => view.push_str(&format!("\n  {}. {}", i + 1, ret.return_type)),
+            }
+        }
+
+        Some(Self {
+            description: None,
+            view,
+            range: self.range,
+            arguments,
+            returns,
+            overloads: Vec::new(),
+            is_async: self.is_async,
+            nodiscard: self.nodiscard,
+        })
+    }
+
+    /// Add another signature for this function, ignoring duplicates.
+    pub(crate) fn add_overload(&mut self, mut overload: Function) {
+        let overloads = std::mem::take(&mut overload.overloads);
+
+        for overload in std::iter::once(overload).chain(overloads) {
+            let duplicate = overload.view == self.view
+                || self.overloads.iter().any(|existing| existing.view == overload.view);
+
+            if !duplicate {
+                self.overloads.push(overload);
+            }
+        }
+    }
+}
+
+/// Combine functions with the same name into one function with overloads,
+/// keeping the order of first declaration.
+fn merge_overloads(functions: impl IntoIterator<Item = NamedFunction>) -> Vec<NamedFunction> {
+    let mut merged: Vec<NamedFunction> = Vec::new();
+
+    for function in functions {
+        match merged.iter_mut().find(|f| f.name == function.name) {
+            Some(existing) => existing.function.add_overload(function.function),
+            None => merged.push(function),
+        }
+    }
+
+    merged
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct NamedFunction {
-    pub name: String,
+    pub(crate) name: String,
+    pub(crate) deprecated: Option<Deprecation>,
+    pub(crate) visibility: Visibility,
     #[serde(flatten)]
-    pub function: Function,
+    pub(crate) function: Function,
 }
 
 impl NamedFunction {
-    pub fn parse(field: &json::Field) -> Result<Self> {
+    pub(crate) fn new(name: String, function: Function) -> Self {
+        Self {
+            name,
+            deprecated: None,
+            visibility: Visibility::Public,
+            function,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn deprecated(&self) -> Option<&Deprecation> {
+        self.deprecated.as_ref()
+    }
+
+    pub fn visibility(&self) -> Visibility {
+        self.visibility
+    }
+
+    pub fn function(&self) -> &Function {
+        &self.function
+    }
+
+    pub(crate) fn parse(field: &json::Field) -> Result<Self> {
         ensure!(field.field_type == FieldType::SetMethod);
+
+        Self::from_field(field)
+    }
+
+    /// Parse a method or a function assigned to a field.
+    fn from_field(field: &json::Field) -> Result<Self> {
         ensure!(field.extends.extends_type == ExtendsType::Function);
 
         Ok(NamedFunction {
             name: field.name.clone(),
+            deprecated: Deprecation::from_flag(field.deprecated.or(field.extends.deprecated)),
+            visibility: Visibility::parse(field.visible.as_ref()),
             function: Function::parse(&field.extends)?,
         })
     }
 }
 
+/// Marks an item or member that shouldn't be used anymore.
+#[derive(Clone, Debug, Default, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct Deprecation {
+    /// What to use instead, when the `---@deprecated` annotation says.
+    pub(crate) message: Option<String>,
+}
+
+impl Deprecation {
+    pub fn message(&self) -> Option<&str> {
+        self.message.as_deref()
+    }
+
+    /// The deprecation LuaLS reports, which never has a message.
+    pub(crate) fn from_flag(deprecated: Option<bool>) -> Option<Self> {
+        deprecated.unwrap_or(false).then(Self::default)
+    }
+}
+
+/// Where a member can be used, from `---@private`, `---@protected` and
+/// `---@package`. Ordered from most to least visible.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[non_exhaustive]
+pub enum Visibility {
+    #[default]
+    Public,
+    /// Only in the class and its subclasses.
+    Protected,
+    /// Only in the file declaring it.
+    Package,
+    /// Only in the class.
+    Private,
+}
+
+impl Visibility {
+    fn parse(visible: Option<&json::Visibility>) -> Self {
+        match visible {
+            None | Some(json::Visibility::Public) => Self::Public,
+            Some(json::Visibility::Protected) => Self::Protected,
+            Some(json::Visibility::Package) => Self::Package,
+            Some(json::Visibility::Private) => Self::Private,
+        }
+    }
+}
+
 #[derive(Clone, Debug, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct Argument {
-    pub name: Option<String>,
-    pub description: Option<String>,
+    pub(crate) name: Option<String>,
+    pub(crate) description: Option<String>,
     #[serde(rename = "type")]
-    pub arg_type: String,
+    pub(crate) arg_type: LuaType,
 }
 
 impl Argument {
-    pub fn parse(arg: &json::FuncArg) -> Result<Self> {
+    /// The argument name, which is missing for varargs.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    pub fn arg_type(&self) -> &LuaType {
+        &self.arg_type
+    }
+
+    pub(crate) fn parse(arg: &json::FuncArg) -> Result<Self> {
         let arg_type = match arg.arg_type {
-            ArgType::DocType => arg.view.clone(),
-            ArgType::Local => arg.view.clone(),
-            ArgType::SelfType => "self".to_string(),
-            ArgType::VarArg => "...".to_string(),
+            ArgType::DocType => LuaType::from_view(&arg.view),
+            ArgType::Local => LuaType::from_view(&arg.view),
+            ArgType::SelfType => LuaType::name("self"),
+            ArgType::VarArg => LuaType::Variadic {
+                inner: Box::new(LuaType::from_view(&arg.view)),
+            },
+            // Reported when building the docs, the view is still the best guess
+            ArgType::Other(_) => LuaType::from_view(&arg.view),
         };
 
         Ok(Self {
@@ -451,18 +1331,259 @@ impl Argument {
 
 #[derive(Clone, Debug, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct Return {
-    pub name: Option<String>,
+    pub(crate) name: Option<String>,
     #[serde(rename = "type")]
-    pub return_type: String,
-    pub description: Option<String>,
+    pub(crate) return_type: LuaType,
+    pub(crate) description: Option<String>,
 }
 
 impl Return {
-    pub fn parse(ret: &json::FuncReturn) -> Result<Self> {
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    pub fn return_type(&self) -> &LuaType {
+        &self.return_type
+    }
+
+    pub(crate) fn parse(ret: &json::FuncReturn) -> Result<Self> {
         Ok(Self {
             name: ret.name.clone(),
             description: ret.rawdesc.clone(),
-            return_type: ret.view.clone(),
+            return_type: LuaType::from_view(&ret.view),
         })
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        diagnostics::Severity,
+        testing::{function, item, range},
+        workspace::SourceFile,
+    };
+
+    #[test]
+    fn parse_class_parents() -> Result<()> {
+        let definition: Definition = serde_json::from_str(r#"{
+            "name": "Child",
+            "type": "type",
+            "defines": [
+                {
+                    "file": "file:///library/child.lua",
+                    "start": 10010,
+                    "finish": 10015,
+                    "type": "doc.class",
+                    "extends": [
+                        {
+                            "start": 10018,
+                            "finish": 10024,
+                            "type": "doc.extends.name",
+                            "view": "Parent"
+                        }
+                    ]
+                }
+            ],
+            "fields": []
+        }"#)?;
+
+        let class = Class::parse(&definition)?;
+
+        assert_eq!(class.parents, vec![LuaType::name("Parent")]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn collect_diagnostics() -> Result<()> {
+        let uri = FileUri::parse("file:///library/globals.lua")?;
+        let mut source_file = SourceFile::new(uri.clone(), "bad_global = nil\ntbl = {}\ntbl.x = 1\n".to_string());
+        let definitions: Vec<Definition> = serde_json::from_str(r#"[
+            {
+                "name": "bad_global",
+                "type": "variable",
+                "defines": [{ "file": "file:///library/globals.lua", "start": 0, "finish": 10, "type": "setglobal" }]
+            },
+            {
+                "name": "tbl",
+                "type": "variable",
+                "defines": [{
+                    "file": "file:///library/globals.lua", "start": 10000, "finish": 10003, "type": "setglobal",
+                    "extends": [{ "start": 10006, "finish": 10008, "type": "table", "view": "table" }]
+                }]
+            },
+            {
+                "name": "tbl.__add",
+                "type": "variable",
+                "defines": [{ "file": "file:///library/globals.lua", "start": 20000, "finish": 20005, "type": "doc.operator" }]
+            },
+            {
+                "name": "tbl.x",
+                "type": "variable",
+                "defines": [{
+                    "file": "file:///library/globals.lua", "start": 20000, "finish": 20005, "type": "setfield",
+                    "extends": [{ "start": 20008, "finish": 20009, "type": "doc.type", "view": "integer" }]
+                }]
+            }
+        ]"#)?;
+        for definition in definitions {
+            source_file.add_definition(definition)?;
+        }
+        let mut workspace = Workspace::new(FileUri::parse("file:///library")?);
+        workspace.files.insert(uri, source_file);
+
+        let (tree, diagnostics) = build_docs_with_diagnostics(workspace.clone());
+
+        assert_eq!(tree.files()[0].items().map(|item| item.name()).collect::<Vec<_>>(), vec!["tbl"]);
+        let found: Vec<(Severity, u64, &str)> = diagnostics
+            .iter()
+            .map(|diagnostic| (diagnostic.severity, diagnostic.location.range.start().line, diagnostic.message.as_str()))
+            .collect();
+        assert_eq!(found, vec![
+            (Severity::Warning, 2, "Unknown LuaLS define type \"doc.operator\" in tbl.__add: {\"file\":\"file:///library/globals.lua\",\"finish\":20005,\"start\":20000,\"type\":\"doc.operator\"}"),
+            (Severity::Error, 0, "Unable to document bad_global: expected extends for setglobal"),
+            (Severity::Warning, 2, "Unexpected setfield type DocType"),
+        ]);
+        assert!(build_docs(workspace).unwrap_err().to_string().contains("   1 | bad_global = nil"));
+
+        Ok(())
+    }
+
+    fn member(name: &str, value: &str, line: u64) -> EnumMember {
+        EnumMember::new(name.to_string(), None, Some(value.to_string()), range(line))
+    }
+
+    #[test]
+    fn infer_enum_type() {
+        let mut lua_enum = Enum::default();
+        assert_eq!(lua_enum.enum_type, LuaType::name("unknown"));
+
+        lua_enum.add_field(member("black", "0", 0));
+        lua_enum.add_field(member("red", "0x10", 1));
+        assert_eq!(lua_enum.enum_type, LuaType::name("integer"));
+
+        lua_enum.add_field(member("blue", "\"blue\"", 2));
+        assert_eq!(
+            lua_enum.enum_type,
+            LuaType::Union { types: vec![LuaType::name("integer"), LuaType::name("string")] }
+        );
+    }
+
+    #[test]
+    fn keep_table_function_overloads() {
+        let mut table = Table::default();
+        let tohex = |view: &str| NamedFunction::new("tohex".to_string(), function(view));
+
+        table.add_function(tohex("function bit.tohex(x: integer, n: integer)"));
+        table.add_function(tohex("function bit.tohex(x: integer)"));
+        table.add_function(tohex("function bit.tohex(x: integer)"));
+
+        let tohex = &table.functions["tohex"].function;
+        assert_eq!(tohex.view, "function bit.tohex(x: integer, n: integer)");
+        assert_eq!(tohex.overloads.len(), 1);
+        assert_eq!(tohex.overloads[0].view, "function bit.tohex(x: integer)");
+    }
+
+    #[test]
+    fn build_overload_from_signature() {
+        let tohex = function("function bit.tohex(x: integer, n: integer)\n  -> string");
+        let signature = LuaType::parse("fun(x: integer, n?: integer):string").unwrap();
+
+        let overload = tohex.overload(&signature).unwrap();
+
+        assert_eq!(overload.view, "function bit.tohex(x: integer, n?: integer)\n  -> string");
+        assert_eq!(overload.arguments[1].arg_type.to_string(), "integer?");
+        assert_eq!(overload.returns[0].return_type, LuaType::name("string"));
+        assert!(tohex.overload(&LuaType::name("string")).is_none());
+    }
+
+    #[test]
+    fn sort_items() {
+        let mut lua_enum = Enum::default();
+        lua_enum.add_field(member("red", "1", 2));
+        lua_enum.add_field(member("black", "0", 1));
+        lua_enum.add_field(member("green", "2", 3));
+
+        let mut file = MetaFile::new(FileUri::parse("file:///library/colors.lua").unwrap());
+        for (name, line) in [("colors", 4), ("Color", 0)] {
+            file.add_item(DocItem {
+                range: range(line),
+                ..item(name, DocItemEnum::Enum(lua_enum.clone()))
+            });
+        }
+
+        let names = |file: &MetaFile| -> Vec<String> {
+            let DocItemEnum::Enum(lua_enum) = &file.items["colors"].inner else { unreachable!() };
+            file.items.keys().chain(lua_enum.fields.keys()).cloned().collect()
+        };
+
+        file.sort(ItemOrder::Source);
+        assert_eq!(names(&file), ["Color", "colors", "black", "red", "green"]);
+
+        file.sort(ItemOrder::Alphabetical);
+        assert_eq!(names(&file), ["Color", "colors", "black", "green", "red"]);
+    }
+
+    #[test]
+    fn filter_members_by_visibility() {
+        let mut class = Class::default();
+        for (name, visibility) in [
+            ("id", Visibility::Public),
+            ("_cache", Visibility::Public),
+            ("parent", Visibility::Protected),
+            ("handle", Visibility::Private),
+        ] {
+            let mut field = Field::new(name.to_string(), None, LuaType::name("string"), range(0));
+            field.visibility = visibility;
+            class.fields.push(field);
+        }
+        let mut file = MetaFile::new(FileUri::parse("file:///library/node.lua").unwrap());
+        file.add_item(item("Node", DocItemEnum::Class(class)));
+        let mut tree = DocTree::new();
+        tree.add_item(file);
+
+        let names = |tree: &DocTree| -> Vec<String> {
+            let class = tree.files()[0].items["Node"].as_class().unwrap();
+            class.fields.iter().map(|field| field.name.clone()).collect()
+        };
+
+        tree.filter_members(VisibilityFilter::All, false);
+        assert_eq!(names(&tree), ["id", "_cache", "parent", "handle"]);
+
+        tree.filter_members(VisibilityFilter::Protected, true);
+        assert_eq!(names(&tree), ["id", "parent"]);
+
+        tree.filter_members(VisibilityFilter::default(), false);
+        assert_eq!(names(&tree), ["id"]);
+        assert!("private".parse::<VisibilityFilter>().is_err());
+    }
+
+    #[test]
+    fn item_pages_apart_from_module_pages() -> Result<()> {
+        let base = Path::new("/library");
+        let mut renoise = MetaFile::new(FileUri::parse("file:///library/renoise.lua")?);
+        for name in ["song", "Map<K, V>"] {
+            renoise.add_item(item(name, DocItemEnum::Class(Class::default())));
+        }
+        let song = MetaFile::new(FileUri::parse("file:///library/renoise/song.lua")?);
+
+        let item_page = renoise.item_page_path(base, &renoise.items["song"])?;
+        assert_eq!(item_page, PathBuf::from("renoise/song.item.md"));
+        assert_ne!(item_page, song.page_path(base)?);
+        assert_eq!(renoise.item_page_path(base, &renoise.items["Map<K, V>"])?, PathBuf::from("renoise/Map_K__V_.item.md"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn parse_item_order() {
+        assert_eq!("source".parse::<ItemOrder>().unwrap(), ItemOrder::Source);
+        assert_eq!("alphabetical".parse::<ItemOrder>().unwrap(), ItemOrder::Alphabetical);
+        assert!("random".parse::<ItemOrder>().is_err());
+    }
+}
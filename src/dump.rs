@@ -0,0 +1,163 @@
+//! Writes the processed docs as versioned JSON, for tools that want a simpler
+//! input than the LuaLS `doc.json`.
+//!
+//! The format is a single object:
+//!
+//! - `format_version`: the version of this format, [FORMAT_VERSION]. It's
+//!   increased whenever a field is removed or changes meaning. New fields may
+//!   be added without a new version.
+//! - `generator`: the `name` and `version` of the crate that wrote the dump.
+//! - `files`: the documented files, each with:
+//!   - `path`: the path of the definition file relative to the library, with
+//!     `/` separators, such as `renoise/song.lua`.
+//!   - `items`: an object of the items in the file keyed by name, in page order.
+//!   - `children`: the files in the directory of the same name.
+//!
+//! Every item has a `name`, a `description` (or `null`), a `range`, a
+//! `deprecated` object with an optional `message` (or `null`) and a `kind`,
+//! one of `Class`, `Table`, `TypeAlias`, `Enum` or `Global`. A range has a
+//! `start` and `end`, each with a zero-based `line` and `character`. The rest
+//! of the item depends on the kind:
+//!
+//! - `Class`: `parents` (types), `fields`, `methods` and `inherited`, a list of
+//!   the `fields` and `methods` inherited from each ancestor `class`.
+//! - `Table`: the LuaLS `view` of the table, plus `fields` and `functions`
+//!   objects keyed by name.
+//! - `TypeAlias`: the aliased `type`.
+//! - `Enum`: the `type` of the members, and a `fields` object of members keyed
+//!   by name, each with a `value` as written in the source and its `type`.
+//! - `Global`: either `"global": "Primitive"` with a `type`, or
+//!   `"global": "Function"` with the function properties.
+//!
+//! A field has a `name`, `description`, `type`, `range`, `deprecated` and a
+//! `visibility`, one of `public`, `protected`, `package` or `private`. A
+//! function has a `description`, the LuaLS `view` of its signature, a `range`,
+//! `arguments` and `returns` (each with a `name`, `description` and `type`), a
+//! list of `overloads`, which are functions, and the `is_async` and
+//! `nodiscard` flags. Methods and table functions also have a `name`,
+//! `deprecated` and `visibility`.
+//!
+//! Types are objects with a `kind` and properties for that kind:
+//!
+//! - `name`: a `name`.
+//! - `generic_param`: a `name` and an optional `constraint` type.
+//! - `generic`: a `name` and a list of type `args`.
+//! - `literal`: a `value`, such as `"left"` or `1`.
+//! - `union`: a list of `types`.
+//! - `optional`: the `inner` type.
+//! - `array`: the `element` type.
+//! - `table`: the `key` and `value` types.
+//! - `function`: `params`, each with an optional `name`, `optional` and an
+//!   optional `type`, and a list of `returns` types.
+//! - `object`: `fields`, each with a `name`, `optional` and `type`.
+//! - `variadic`: the `inner` type.
+//! - `elided`: the `count` of types LuaLS left out.
+//! - `unknown`: the LuaLS `view` of a type that couldn't be parsed.
+
+use std::io::Write;
+use std::path::Path;
+
+use indexmap::IndexMap;
+use itertools::Itertools;
+use serde::Serialize;
+
+use crate::{
+    doctree::{DocItem, DocTree, MetaFile},
+    errors::*,
+    generate::{load_docs, DocOptions},
+};
+
+/// The version of the dump format.
+pub const FORMAT_VERSION: u32 = 1;
+
+#[derive(Serialize)]
+struct Dump<'a> {
+    format_version: u32,
+    generator: Generator,
+    files: Vec<DumpFile<'a>>,
+}
+
+#[derive(Serialize)]
+struct DumpFile<'a> {
+    path: String,
+    items: &'a IndexMap<String, DocItem>,
+    children: Vec<DumpFile<'a>>,
+}
+
+impl<'a> DumpFile<'a> {
+    fn new(file: &'a MetaFile, library: &Path) -> Result<Self> {
+        let path = file.uri().to_file_path()?;
+        let path = path
+            .strip_prefix(library)
+            .map_err(|_| anyhow!("{} isn't in the library", path.display()))?;
+
+        Ok(Self {
+            path: path.components().map(|component| component.as_os_str().to_string_lossy()).join("/"),
+            items: &file.items,
+            children: file.children().iter().map(|child| Self::new(child, library)).try_collect()?,
+        })
+    }
+}
+
+#[derive(Serialize)]
+struct Generator {
+    name: &'static str,
+    version: &'static str,
+}
+
+/// Build the docs for the library at the given path and write them as JSON.
+pub fn dump_docs(library: &Path, options: &DocOptions, writer: impl Write) -> Result<()> {
+    let doc_tree = load_docs(library, options)?;
+
+    write_dump(&doc_tree, library, writer)
+}
+
+fn write_dump(doc_tree: &DocTree, library: &Path, writer: impl Write) -> Result<()> {
+    let dump = Dump {
+        format_version: FORMAT_VERSION,
+        generator: Generator {
+            name: env!("CARGO_PKG_NAME"),
+            version: env!("CARGO_PKG_VERSION"),
+        },
+        files: doc_tree.files().iter().map(|file| DumpFile::new(file, library)).try_collect()?,
+    };
+
+    serde_json::to_writer_pretty(writer, &dump)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::{json, Value};
+
+    use super::*;
+    use crate::location::PathRemap;
+
+    #[test]
+    fn dump_test_data() -> Result<()> {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let options = DocOptions {
+            doc_json_path: Some(root.join("testdata/doc.json")),
+            path_remaps: vec![PathRemap::new("/Users/matt/Code/mooncats", root)],
+            ..DocOptions::default()
+        };
+
+        let mut json = Vec::new();
+        dump_docs(&root.join("testdata"), &options, &mut json)?;
+        let dump: Value = serde_json::from_slice(&json)?;
+
+        assert_eq!(dump["format_version"], json!(FORMAT_VERSION));
+        assert_eq!(dump["generator"]["name"], json!("mooncats"));
+
+        assert_eq!(dump["files"][0]["path"], json!("test.lua"));
+        let items = &dump["files"][0]["items"];
+        assert_eq!(items["AClass"]["kind"], json!("Class"));
+        assert_eq!(items["AClass"]["fields"][0]["type"], json!({ "kind": "name", "name": "string" }));
+        assert_eq!(items["global_int"]["kind"], json!("Global"));
+        assert_eq!(items["global_int"]["global"], json!("Primitive"));
+        assert_eq!(items["global_int"]["range"]["start"], json!({ "line": 2, "character": 0 }));
+
+        Ok(())
+    }
+}
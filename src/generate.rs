@@ -0,0 +1,225 @@
+//! Builds the docs for a library, and writes them to disk without mdbook.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use log::*;
+
+pub use crate::{doctree::{ItemOrder, PageGranularity, VisibilityFilter}, location::PathRemap};
+
+use crate::{
+    doctree::{build_docs_with_diagnostics, DocTree, ItemRef, MetaFile},
+    errors::*,
+    index::render_index_pages,
+    links::rewrite_links,
+    location::FileUri,
+    markdown::MarkdownRenderer,
+    symbols::SymbolIndex,
+    workspace::Workspace,
+};
+
+/// How to read the definitions of a library and build its docs.
+#[derive(Clone, Debug, Default)]
+pub struct DocOptions {
+    /// A `doc.json` file exported by LuaLS. LuaLS is run on the library when
+    /// this isn't set.
+    pub doc_json_path: Option<PathBuf>,
+    /// Rules for rewriting the file paths in the definitions.
+    pub path_remaps: Vec<PathRemap>,
+    /// List the members each class inherits from its ancestors.
+    pub inherited_members: bool,
+    pub item_order: ItemOrder,
+    /// A directory of `.hbs` files that replace the built-in templates of the
+    /// same name.
+    pub template_dir: Option<PathBuf>,
+    pub page_granularity: PageGranularity,
+    /// Add chapters listing every class, function, type, enum and global.
+    pub index_chapters: bool,
+    /// Fail on warnings about the definitions, as well as errors.
+    pub strict: bool,
+    /// The members to document, by their visibility.
+    pub visibility: VisibilityFilter,
+    /// Leave out members with a name starting with `_`.
+    pub hide_underscored: bool,
+}
+
+/// Read the definitions of the library at the given path and build its docs.
+pub fn load_docs(library: &Path, options: &DocOptions) -> Result<DocTree> {
+    let root_uri: FileUri = library.to_path_buf().try_into()?;
+
+    let mut workspace = Workspace::new(root_uri);
+    for remap in options.path_remaps.iter() {
+        debug!("Remapping paths from {:?} to {:?}", remap.from, remap.to);
+        workspace.add_remap(remap.clone());
+    }
+
+    match &options.doc_json_path {
+        Some(doc_json_path) => {
+            debug!("Using doc.json path: {:?}", doc_json_path);
+            workspace.load_json_file(doc_json_path)?;
+        },
+        None => workspace.load_luals()?,
+    }
+    debug!("Loaded {} files", workspace.len());
+
+    let (mut doc_tree, diagnostics) = build_docs_with_diagnostics(workspace);
+    diagnostics.check(options.strict)?;
+
+    doc_tree.filter_members(options.visibility, options.hide_underscored);
+    if options.inherited_members {
+        doc_tree.add_inherited_members();
+    }
+    doc_tree.sort(options.item_order);
+
+    Ok(doc_tree)
+}
+
+/// Create a renderer for the symbols, using any templates in the configured
+/// template directory.
+pub(crate) fn renderer<'a>(symbols: Arc<SymbolIndex>, options: &DocOptions) -> Result<MarkdownRenderer<'a>> {
+    let mut md = MarkdownRenderer::with_symbols(symbols);
+    md.set_page_granularity(options.page_granularity);
+    if let Some(template_dir) = &options.template_dir {
+        debug!("Using template directory: {:?}", template_dir);
+        md.load_templates(template_dir)?;
+    }
+
+    Ok(md)
+}
+
+/// Write a markdown page for each file in the library to the output
+/// directory, along with a `SUMMARY.md` fragment listing the pages. Returns
+/// the paths of the files written.
+pub fn generate(library: &Path, out: &Path, options: &DocOptions) -> Result<Vec<PathBuf>> {
+    let doc_tree = load_docs(library, options)?;
+
+    let symbols = Arc::new(SymbolIndex::build_with_pages(&doc_tree, library, options.page_granularity)?);
+    debug!("Indexed {} symbols", symbols.len());
+    let md = renderer(symbols.clone(), options)?;
+
+    let mut writer = PageWriter {
+        md: &md,
+        symbols: &symbols,
+        library,
+        out,
+        granularity: options.page_granularity,
+        summary: String::new(),
+        written: Vec::new(),
+    };
+    writer.write_pages(doc_tree.files(), 0)?;
+
+    if options.index_chapters {
+        for page in render_index_pages(&doc_tree, library, &md)? {
+            writer.write_page(page.title, &page.path, &page.content, 0)?;
+        }
+    }
+
+    let summary_path = out.join("SUMMARY.md");
+    fs::write(&summary_path, &writer.summary)
+        .map_err(|e| anyhow!("Unable to write {}: {}", summary_path.display(), e))?;
+    writer.written.push(summary_path);
+
+    Ok(writer.written)
+}
+
+/// Writes pages and collects the summary entries for them.
+struct PageWriter<'a> {
+    md: &'a MarkdownRenderer<'a>,
+    symbols: &'a SymbolIndex,
+    library: &'a Path,
+    out: &'a Path,
+    granularity: PageGranularity,
+    summary: String,
+    written: Vec<PathBuf>,
+}
+
+impl PageWriter<'_> {
+    fn write_pages(&mut self, files: &[MetaFile], depth: usize) -> Result<()> {
+        for file in files {
+            let page = file.page_path(self.library)?;
+            self.write_page(&file.uri.file_stem(), &page, &self.md.render_meta(file)?, depth)?;
+
+            for item in file.page_items(self.granularity) {
+                let page = file.item_page_path(self.library, item)?;
+                self.write_page(&item.name, &page, &self.md.render_item(ItemRef::Item(item), &page)?, depth + 1)?;
+            }
+
+            self.write_pages(&file.children, depth + 1)?;
+        }
+
+        Ok(())
+    }
+
+    fn write_page(&mut self, name: &str, page: &Path, content: &str, depth: usize) -> Result<()> {
+        let (content, missing) = rewrite_links(content, page, self.symbols);
+        for name in missing {
+            warn!("{}: link to missing item {:?}", page.display(), name);
+        }
+
+        let path = self.out.join(page);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .map_err(|e| anyhow!("Unable to create {}: {}", dir.display(), e))?;
+        }
+        fs::write(&path, content)
+            .map_err(|e| anyhow!("Unable to write {}: {}", path.display(), e))?;
+        self.written.push(path);
+
+        self.summary.push_str(&format!("{}- [{}]({})\n", "  ".repeat(depth), name, page.display()));
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use tempdir::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn generate_pages() -> Result<()> {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let out = TempDir::new("mooncats-generate")?;
+        let options = DocOptions {
+            doc_json_path: Some(root.join("testdata/doc.json")),
+            path_remaps: vec![PathRemap::new("/Users/matt/Code/mooncats", root)],
+            ..DocOptions::default()
+        };
+
+        let written = generate(&root.join("testdata"), out.path(), &options)?;
+
+        assert_eq!(written, vec![out.path().join("test.md"), out.path().join("SUMMARY.md")]);
+        assert!(fs::read_to_string(out.path().join("test.md"))?.contains("### AClass <a id=\"AClass\"></a>"));
+        assert_eq!(fs::read_to_string(out.path().join("SUMMARY.md"))?, "- [test](test.md)\n");
+
+        Ok(())
+    }
+
+    #[test]
+    fn generate_item_pages() -> Result<()> {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let out = TempDir::new("mooncats-generate")?;
+        let options = DocOptions {
+            doc_json_path: Some(root.join("testdata/doc.json")),
+            path_remaps: vec![PathRemap::new("/Users/matt/Code/mooncats", root)],
+            page_granularity: PageGranularity::Item,
+            ..DocOptions::default()
+        };
+
+        generate(&root.join("testdata"), out.path(), &options)?;
+
+        let summary = fs::read_to_string(out.path().join("SUMMARY.md"))?;
+        assert_eq!(summary, "- [test](test.md)\n  - [global_table](test/global_table.item.md)\n  - [AClass](test/AClass.item.md)\n");
+
+        let file_page = fs::read_to_string(out.path().join("test.md"))?;
+        assert!(file_page.contains("- [`AClass`](test/AClass.item.md#AClass)"), "{}", file_page);
+        assert!(!file_page.contains("### AClass"), "{}", file_page);
+
+        let item_page = fs::read_to_string(out.path().join("test/AClass.item.md"))?;
+        assert!(item_page.starts_with("### AClass <a id=\"AClass\"></a>"), "{}", item_page);
+
+        Ok(())
+    }
+}
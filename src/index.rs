@@ -0,0 +1,231 @@
+//! Builds the index chapters, which list every item of a kind across the
+//! whole library.
+
+use std::path::{Path, PathBuf};
+
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    doctree::{DocItem, DocItemEnum, DocTree, Global, MetaFile},
+    errors::*,
+    markdown::MarkdownRenderer,
+    symbols::relative_path,
+};
+
+/// The kinds of item with an index chapter.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum IndexKind {
+    Classes,
+    Functions,
+    Types,
+    Enums,
+    Globals,
+}
+
+impl IndexKind {
+    pub(crate) const ALL: [IndexKind; 5] = [Self::Classes, Self::Functions, Self::Types, Self::Enums, Self::Globals];
+
+    pub(crate) fn title(self) -> &'static str {
+        match self {
+            Self::Classes => "All Classes",
+            Self::Functions => "All Functions",
+            Self::Types => "All Types",
+            Self::Enums => "All Enums",
+            Self::Globals => "All Globals",
+        }
+    }
+
+    /// The chapter path. The names can't clash with the pages of Lua modules,
+    /// which can't be required with a `-` in their name.
+    pub(crate) fn page_path(self) -> PathBuf {
+        let name = match self {
+            Self::Classes => "all-classes",
+            Self::Functions => "all-functions",
+            Self::Types => "all-types",
+            Self::Enums => "all-enums",
+            Self::Globals => "all-globals",
+        };
+
+        PathBuf::from(name).with_extension("md")
+    }
+
+    /// The names and descriptions of the item and its members that are
+    /// listed in this index. Functions include methods and table functions.
+    fn entries(self, item: &DocItem) -> Vec<(String, Option<String>)> {
+        let entry = || vec![(item.name.clone(), item.description.clone())];
+
+        match (self, &item.inner) {
+            (Self::Classes, DocItemEnum::Class(_)) => entry(),
+            (Self::Types, DocItemEnum::TypeAlias(_)) => entry(),
+            (Self::Enums, DocItemEnum::Enum(_)) => entry(),
+            (Self::Globals, DocItemEnum::Table(_) | DocItemEnum::Global(Global::Primitive(_))) => entry(),
+            (Self::Functions, DocItemEnum::Global(Global::Function(function))) => {
+                vec![(item.name.clone(), item.description.clone().or_else(|| function.description.clone()))]
+            },
+            (Self::Functions, DocItemEnum::Class(class)) => class
+                .methods
+                .iter()
+                .map(|method| (format!("{}.{}", item.name, method.name), method.function.description.clone()))
+                .collect(),
+            (Self::Functions, DocItemEnum::Table(table)) => table
+                .functions
+                .values()
+                .map(|function| (format!("{}.{}", item.name, function.name), function.function.description.clone()))
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// An item listed in an index chapter.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub(crate) struct IndexEntry {
+    pub name: String,
+    pub description: Option<String>,
+    /// The Lua module of the file declaring the item, such as `renoise.song`.
+    pub module: String,
+    /// A link to the page of the file, relative to the index chapter.
+    pub module_link: String,
+}
+
+/// A rendered index chapter.
+pub(crate) struct IndexPage {
+    pub title: &'static str,
+    pub path: PathBuf,
+    pub content: String,
+}
+
+/// Render an index chapter for each kind of item in the tree, skipping kinds
+/// with no items.
+pub(crate) fn render_index_pages(tree: &DocTree, base: &Path, md: &MarkdownRenderer) -> Result<Vec<IndexPage>> {
+    let mut pages = Vec::new();
+
+    for kind in IndexKind::ALL {
+        let path = kind.page_path();
+        let entries = index_entries(tree, base, kind, &path)?;
+        if entries.is_empty() {
+            continue
+        }
+
+        pages.push(IndexPage {
+            title: kind.title(),
+            content: md.render_index(kind.title(), &entries, &path)?,
+            path,
+        });
+    }
+
+    Ok(pages)
+}
+
+/// Every entry of the kind in the tree, sorted by name.
+fn index_entries(tree: &DocTree, base: &Path, kind: IndexKind, page: &Path) -> Result<Vec<IndexEntry>> {
+    let mut entries = Vec::new();
+    let mut result = Ok(());
+
+    tree.for_each(|file: &MetaFile| {
+        if result.is_err() {
+            return
+        }
+
+        let file_page = match file.page_path(base) {
+            Ok(file_page) => file_page,
+            Err(e) => {
+                result = Err(e);
+                return
+            },
+        };
+        let module = file_page
+            .with_extension("")
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .join(".");
+        let module_link = relative_path(page, &file_page).to_string_lossy().to_string();
+
+        for item in file.items.values() {
+            for (name, description) in kind.entries(item) {
+                entries.push(IndexEntry {
+                    name,
+                    description,
+                    module: module.clone(),
+                    module_link: module_link.clone(),
+                });
+            }
+        }
+    });
+    result?;
+
+    entries.sort_by(|a, b| {
+        a.name
+            .to_lowercase()
+            .cmp(&b.name.to_lowercase())
+            .then_with(|| a.name.cmp(&b.name))
+            .then_with(|| a.module.cmp(&b.module))
+    });
+
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::{
+        doctree::{Class, Function, NamedFunction, TypeAlias},
+        location::FileUri,
+        lua_type::LuaType,
+        symbols::SymbolIndex,
+        testing::function,
+    };
+
+    fn item(name: &str, inner: DocItemEnum) -> DocItem {
+        DocItem {
+            description: Some(format!("About {}. More.", name)),
+            ..crate::testing::item(name, inner)
+        }
+    }
+
+    #[test]
+    fn index_all_items() -> Result<()> {
+        let function = Function {
+            description: Some("Does it.".to_string()),
+            ..function("function ()")
+        };
+        let mut song = MetaFile::new(FileUri::parse("file:///library/renoise/song.lua")?);
+        song.add_item(item("renoise.Song", DocItemEnum::Class(Class {
+            methods: vec![NamedFunction::new("save".to_string(), function.clone())],
+            ..Class::default()
+        })));
+        let mut renoise = MetaFile::new(FileUri::parse("file:///library/renoise.lua")?);
+        renoise.add_item(item("renoise.Application", DocItemEnum::Class(Class::default())));
+        renoise.add_item(item("renoise.app", DocItemEnum::Global(Global::Function(function))));
+        renoise.add_item(item("Color", DocItemEnum::TypeAlias(TypeAlias { aliased_type: LuaType::name("integer") })));
+        renoise.children.push(song);
+        let mut tree = DocTree::new();
+        tree.add_item(renoise);
+
+        let base = Path::new("/library");
+        let md = MarkdownRenderer::with_symbols(Arc::new(SymbolIndex::build(&tree, base)?));
+        let pages = render_index_pages(&tree, base, &md)?;
+
+        let titles: Vec<(&str, PathBuf)> = pages.iter().map(|page| (page.title, page.path.clone())).collect();
+        assert_eq!(titles, vec![
+            ("All Classes", PathBuf::from("all-classes.md")),
+            ("All Functions", PathBuf::from("all-functions.md")),
+            ("All Types", PathBuf::from("all-types.md")),
+        ]);
+        assert_eq!(
+            pages[0].content,
+            "# All Classes\n\n\
+            | Name | Module | Description |\n\
+            | ---- | ------ | ----------- |\n\
+            | [`renoise.Application`](renoise.md#renoise.Application) | [renoise](renoise.md) | About renoise.Application. |\n\
+            | [`renoise.Song`](renoise/song.md#renoise.Song) | [renoise.song](renoise/song.md) | About renoise.Song. |\n"
+        );
+        assert!(pages[1].content.contains("| [`renoise.app`](renoise.md#renoise.app) | [renoise](renoise.md) | About renoise.app. |\n"));
+        assert!(pages[1].content.contains("| [`renoise.Song.save`](renoise/song.md#renoise.Song.save) | [renoise.song](renoise/song.md) | Does it. |\n"));
+
+        Ok(())
+    }
+}
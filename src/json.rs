@@ -1,12 +1,52 @@
 //!Type definitions for the JSON data types used by the LuaLS `doc.json` files.
 //! https://luals.github.io/wiki/export-docs/
 
-use std::{fmt, marker::PhantomData};
+use std::{fmt, fs, marker::PhantomData, path::Path, process::Command};
 
-use serde::{de::{self, MapAccess, Visitor}, Deserialize, Deserializer, Serialize};
+use serde::{de::{self, DeserializeOwned, MapAccess, Visitor}, Deserialize, Deserializer, Serialize};
+use serde_json::Value;
 use nonempty::NonEmpty;
+use tempdir::TempDir;
 
-use crate::{location::{Location, Range}};
+use crate::{errors::*, location::{FileUri, Location, Range}};
+
+/// Read the definitions from a `doc.json` file exported by LuaLS.
+pub fn read_json_docs(path: &Path) -> Result<Vec<Definition>> {
+    let json_doc = fs::read_to_string(path)
+        .map_err(|e| anyhow!("Unable to read {}: {}", path.display(), e))?;
+
+    let definitions: Vec<Definition> = serde_json::from_str(&json_doc)
+        .map_err(|e| anyhow!("Invalid doc.json {}: {}", path.display(), e))?;
+
+    Ok(definitions)
+}
+
+/// Spawn the lua-language-server to generate docs.
+pub fn generate_json_docs(definitions_path: &Path) -> Result<Vec<Definition>> {
+    let tmp_dir = TempDir::new("luals-docs")?;
+    let tmp_path = tmp_dir.path();
+
+    let output = Command::new("lua-language-server")
+        .arg("--doc")
+        .arg(definitions_path)
+        .arg("--doc_out_path")
+        .arg(tmp_path)
+        .arg("--logpath")
+        .arg(tmp_path)
+        .output()?;
+
+    if !output.status.success() {
+        let err = match output.status.code() {
+            Some(code) => anyhow!("LuaLS process exited with status code {}", code),
+            None => anyhow!("LuaLS process terminated by signal"),
+        };
+        return Err(err)
+    }
+
+    let json_doc_path = tmp_dir.path().join("doc.json");
+
+    read_json_docs(&json_doc_path)
+}
 
 #[derive(Clone, Debug, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct Definition {
@@ -15,11 +55,29 @@ pub struct Definition {
     #[serde(rename = "type")]
     pub definition_type: DefinitionType,
     pub rawdesc: Option<String>,
+    #[serde(deserialize_with = "deserialize_defines")]
     pub defines: NonEmpty<Define>,
     #[serde(default)]
+    #[serde(deserialize_with = "deserialize_tagged_vec")]
     pub fields: Vec<Field>,
 }
 
+impl Definition {
+    /// Rewrite the file URI of every define and field.
+    pub fn map_files<F>(&mut self, mut func: F)
+    where
+        F: FnMut(&FileUri) -> FileUri,
+    {
+        for define in self.defines.iter_mut() {
+            define.location.file = func(&define.location.file);
+        }
+
+        for field in self.fields.iter_mut() {
+            field.location.file = func(&field.location.file);
+        }
+    }
+}
+
 #[derive(Clone, Debug, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
 
 #[serde(rename_all = "lowercase")]
@@ -37,6 +95,9 @@ pub struct Define {
     #[serde(default)]
     #[serde(deserialize_with = "deserialize_extends")]
     pub extends: Vec<Extends>,
+    /// The JSON of a define with an unknown type.
+    #[serde(skip)]
+    pub raw: Option<String>,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
@@ -55,6 +116,9 @@ pub enum DefineType {
     SetField,
     SetMethod,
     SetIndex,
+    /// A type added by a newer version of LuaLS.
+    #[serde(untagged)]
+    Other(String),
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
@@ -64,6 +128,9 @@ pub struct Extends {
     #[serde(rename = "type")]
     pub extends_type: ExtendsType,
     pub view: String,
+    /// Only present for doc types (type = "doc.type")
+    #[serde(default)]
+    pub types: Vec<TypeNode>,
     pub desc: Option<String>,
     pub rawdesc: Option<String>,
     #[serde(rename = "async")]
@@ -71,10 +138,14 @@ pub struct Extends {
     pub deprecated: Option<bool>,
     /// Only present for functions (type = "function") with args
     #[serde(default)]
+    #[serde(deserialize_with = "deserialize_tagged_vec")]
     pub args: Vec<FuncArg>,
     /// Only present for functions (type = "function") with returns
     #[serde(default)]
     pub returns: Vec<FuncReturn>,
+    /// The JSON of an extends with an unknown type.
+    #[serde(skip)]
+    pub raw: Option<String>,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
@@ -91,6 +162,20 @@ pub enum ExtendsType {
     Number,
     String,
     Table,
+    /// A type added by a newer version of LuaLS.
+    #[serde(untagged)]
+    Other(String),
+}
+
+/// A member of a doc type, such as one type within a union.
+#[derive(Clone, Debug, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct TypeNode {
+    #[serde(flatten)]
+    pub range: Range,
+    /// The kind of node, such as "doc.type.name"
+    #[serde(rename = "type")]
+    pub node_type: String,
+    pub view: String,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
@@ -106,7 +191,11 @@ pub struct Field {
     #[serde(rename = "async")]
     pub is_async: Option<bool>,
     pub deprecated: Option<bool>,
+    #[serde(deserialize_with = "deserialize_tagged")]
     pub extends: Extends,
+    /// The JSON of a field with an unknown type.
+    #[serde(skip)]
+    pub raw: Option<String>,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
@@ -116,6 +205,9 @@ pub enum FieldType {
     DocField,
     SetMethod,
     SetField,
+    /// A type added by a newer version of LuaLS.
+    #[serde(untagged)]
+    Other(String),
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
@@ -139,6 +231,9 @@ pub struct FuncArg {
     pub view: String,
     #[serde(flatten)]
     pub range: Range,
+    /// The JSON of an argument with an unknown type.
+    #[serde(skip)]
+    pub raw: Option<String>,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
@@ -151,6 +246,9 @@ pub enum ArgType {
     SelfType,
     #[serde(rename = "...")]
     VarArg,
+    /// A type added by a newer version of LuaLS.
+    #[serde(untagged)]
+    Other(String),
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
@@ -162,6 +260,94 @@ pub struct FuncReturn {
     pub rawdesc: Option<String>,
 }
 
+/// An object with a type that newer versions of LuaLS may add variants to.
+/// Objects with an unknown type keep their JSON, so the type can be reported.
+trait Tagged: DeserializeOwned {
+    fn is_unknown(&self) -> bool;
+
+    fn keep_raw(&mut self, raw: String);
+}
+
+impl Tagged for Define {
+    fn is_unknown(&self) -> bool {
+        matches!(self.define_type, DefineType::Other(_))
+    }
+
+    fn keep_raw(&mut self, raw: String) {
+        self.raw = Some(raw);
+    }
+}
+
+impl Tagged for Extends {
+    fn is_unknown(&self) -> bool {
+        matches!(self.extends_type, ExtendsType::Other(_))
+    }
+
+    fn keep_raw(&mut self, raw: String) {
+        self.raw = Some(raw);
+    }
+}
+
+impl Tagged for Field {
+    fn is_unknown(&self) -> bool {
+        matches!(self.field_type, FieldType::Other(_))
+    }
+
+    fn keep_raw(&mut self, raw: String) {
+        self.raw = Some(raw);
+    }
+}
+
+impl Tagged for FuncArg {
+    fn is_unknown(&self) -> bool {
+        matches!(self.arg_type, ArgType::Other(_))
+    }
+
+    fn keep_raw(&mut self, raw: String) {
+        self.raw = Some(raw);
+    }
+}
+
+fn from_raw<T: Tagged, E: de::Error>(raw: Value) -> Result<T, E> {
+    let mut value = T::deserialize(&raw).map_err(E::custom)?;
+    if value.is_unknown() {
+        value.keep_raw(raw.to_string());
+    }
+
+    Ok(value)
+}
+
+fn deserialize_tagged<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Tagged,
+{
+    from_raw(Value::deserialize(deserializer)?)
+}
+
+fn deserialize_tagged_vec<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Tagged,
+{
+    Vec::<Value>::deserialize(deserializer)?
+        .into_iter()
+        .map(from_raw)
+        .collect()
+}
+
+fn deserialize_defines<'de, D>(deserializer: D) -> Result<NonEmpty<Define>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let defines = NonEmpty::<Value>::deserialize(deserializer)?;
+
+    Ok(NonEmpty {
+        head: from_raw(defines.head)?,
+        tail: defines.tail.into_iter().map(from_raw).collect::<Result<_, _>>()?,
+    })
+}
+
 /// Implement the value of "extends", which may be missing, null, an array
 /// of maps, or a single map. We always deserialize into a vector of maps (which
 /// may be empty) for consistency.
@@ -185,17 +371,26 @@ where
             Ok(Vec::new())
         }
 
+        fn visit_unit<E>(self) -> Result<Self::Value, E>
+            where
+                E: de::Error, {
+            Ok(Vec::new())
+        }
+
         fn visit_seq<A>(self, seq: A) -> Result<Self::Value, A::Error>
             where
                 A: de::SeqAccess<'de>, { 
-            Ok(Deserialize::deserialize(de::value::SeqAccessDeserializer::new(seq))?)
+            Vec::<Value>::deserialize(de::value::SeqAccessDeserializer::new(seq))?
+                .into_iter()
+                .map(from_raw)
+                .collect()
         }
 
         fn visit_map<M>(self, map: M) -> Result<Self::Value, M::Error>
         where
             M: MapAccess<'de>,
         {
-            Ok(vec![Deserialize::deserialize(de::value::MapAccessDeserializer::new(map))?])
+            Ok(vec![from_raw(Value::deserialize(de::value::MapAccessDeserializer::new(map))?)?])
         }
     }
 
@@ -213,8 +408,62 @@ mod test {
 
         let docs: Vec<Definition> = serde_json::from_str(data)?;
 
-        assert!(docs.len() >= 1);
+        assert!(!docs.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn read_json_file() -> Result<(), Box<dyn Error>> {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("testdata/doc.json");
+
+        let docs = read_json_docs(&path)?;
+
+        assert_eq!(docs.len(), 169);
 
         Ok(())
     }
+
+    #[test]
+    fn parse_unknown_types() -> Result<(), Box<dyn Error>> {
+        let definition: Definition = serde_json::from_str(r#"{
+            "name": "Vector",
+            "type": "type",
+            "defines": [
+                { "file": "file:///library/vector.lua", "start": 0, "finish": 5, "type": "doc.operator", "extends": null }
+            ],
+            "fields": [{
+                "name": "scale",
+                "file": "file:///library/vector.lua",
+                "start": 10000,
+                "finish": 10005,
+                "type": "setmethod",
+                "extends": {
+                    "start": 10010,
+                    "finish": 10020,
+                    "type": "function",
+                    "view": "function Vector:scale(by: number)",
+                    "args": [{ "name": "by", "type": "doc.type.function", "view": "number", "start": 10020, "finish": 10022 }]
+                }
+            }]
+        }"#)?;
+
+        let define = &definition.defines.head;
+        assert_eq!(define.define_type, DefineType::Other("doc.operator".to_string()));
+        assert!(define.raw.as_deref().is_some_and(|raw| raw.contains(r#""type":"doc.operator""#)));
+
+        let field = &definition.fields[0];
+        assert_eq!((&field.field_type, &field.raw), (&FieldType::SetMethod, &None));
+        assert_eq!(field.extends.args[0].arg_type, ArgType::Other("doc.type.function".to_string()));
+        assert!(field.extends.args[0].raw.is_some());
+
+        Ok(())
+    }
+
+    #[test]
+    fn read_missing_json_file() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("testdata/missing.json");
+
+        assert!(read_json_docs(&path).is_err());
+    }
 }
\ No newline at end of file
@@ -1,13 +1,64 @@
+//! Builds markdown docs for Lua libraries annotated with LuaCATS, from the
+//! definitions exported by LuaLS.
+//!
+//! ```no_run
+//! use std::path::Path;
+//! use std::sync::Arc;
+//!
+//! use mooncats::{build_docs, FileUri, MarkdownRenderer, SymbolIndex, Workspace};
+//!
+//! # fn main() -> mooncats::errors::Result<()> {
+//! let library = Path::new("library");
+//! let mut workspace = Workspace::new(FileUri::try_from(library.to_path_buf())?);
+//! workspace.load_json_file(Path::new("doc.json"))?;
+//!
+//! let tree = build_docs(workspace)?;
+//! let symbols = Arc::new(SymbolIndex::build(&tree, library)?);
+//! let md = MarkdownRenderer::with_symbols(symbols);
+//!
+//! tree.for_each(|file| {
+//!     for item in file.items() {
+//!         println!("{}: {:?}", item.name(), item.description());
+//!     }
+//! });
+//! let page = md.render_meta(&tree.files()[0])?;
+//! # Ok(())
+//! # }
+//! ```
+
 mod json;
 mod location;
+mod lua_type;
 mod workspace;
 mod markdown;
 mod doctree;
 mod passes;
+mod symbols;
+mod links;
+mod index;
+#[cfg(test)]
+mod testing;
+pub mod generate;
+pub mod coverage;
+pub mod diagnostics;
+pub mod dump;
 pub mod mdbook;
 
+pub use crate::{
+    doctree::{
+        build_docs, build_docs_with_diagnostics, Argument, Class, Deprecation, DocItem, DocItemEnum, DocTree, Enum, EnumMember, Field,
+        Function, Global, InheritedMembers, ItemOrder, ItemRef, MetaFile, NamedFunction, PageGranularity, PrimitiveGlobal, Return,
+        Table, TypeAlias, Visibility, VisibilityFilter,
+    },
+    location::{FileUri, Location, PathRemap, Position, Range},
+    lua_type::{FunctionParam, LuaType, ObjectField},
+    markdown::MarkdownRenderer,
+    symbols::{Symbol, SymbolIndex, SymbolKind},
+    workspace::Workspace,
+};
+
 /// The error types used throughout this crate.
 pub mod errors {
     pub(crate) use anyhow::{anyhow, bail, ensure};
     pub use anyhow::{Error, Result};
-}
\ No newline at end of file
+}
@@ -0,0 +1,145 @@
+//! Rewrites intra-doc links in chapters to the pages of documented items.
+
+use std::ops::Range;
+use std::path::Path;
+
+use pulldown_cmark::{BrokenLink, CowStr, Event, LinkType, Options, Parser, Tag};
+
+use crate::symbols::SymbolIndex;
+
+/// The URL scheme of a link to a documented item, as in
+/// `[renoise.app](luacats:renoise.app)`.
+const LINK_SCHEME: &str = "luacats:";
+
+/// A link found in a chapter.
+struct Link {
+    /// The source range to replace.
+    span: Range<usize>,
+    /// The name of the linked item.
+    target: String,
+    /// The link text as written, including the brackets.
+    label: Option<String>,
+}
+
+/// Rewrite `luacats:` links and links like `` [`bit.tohex`] `` to the chapter
+/// and anchor of the named item, for a chapter at the given path. Returns the
+/// new content and the names of any items that couldn't be found, which are
+/// left as they are.
+pub fn rewrite_links(content: &str, page: &Path, symbols: &SymbolIndex) -> (String, Vec<String>) {
+    let mut links: Vec<Link> = Vec::new();
+    let mut broken_links: Vec<Link> = Vec::new();
+
+    let callback = |link: BrokenLink| -> Option<(CowStr, CowStr)> {
+        let target = link.reference.strip_prefix('`')?.strip_suffix('`')?;
+        let source = &content[link.span.clone()];
+        let (label, end) = match link.link_type {
+            LinkType::Shortcut => (source, link.span.end),
+            // The span doesn't always include the empty brackets
+            LinkType::Collapsed => match source.strip_suffix("[]") {
+                Some(label) => (label, link.span.end),
+                None if content[link.span.end..].starts_with("[]") => (source, link.span.end + 2),
+                None => return None,
+            },
+            LinkType::Reference => (&source[..source.rfind('[')?], link.span.end),
+            _ => return None,
+        };
+
+        broken_links.push(Link {
+            span: link.span.start..end,
+            target: target.to_string(),
+            label: Some(label.to_string()),
+        });
+
+        None
+    };
+
+    let parser = Parser::new_with_broken_link_callback(content, Options::all(), Some(callback));
+    for (event, span) in parser.into_offset_iter() {
+        if let Event::Start(Tag::Link { link_type: LinkType::Inline, dest_url, .. }) = event {
+            if let Some(target) = dest_url.strip_prefix(LINK_SCHEME) {
+                // Only replace the destination, the label may contain other links
+                let Some(start) = content[span.clone()].rfind(dest_url.as_ref()) else {
+                    continue
+                };
+                let start = span.start + start;
+
+                links.push(Link {
+                    span: start..start + dest_url.len(),
+                    target: target.to_string(),
+                    label: None,
+                });
+            }
+        }
+    }
+
+    links.append(&mut broken_links);
+    links.sort_by_key(|link| link.span.start);
+
+    let mut rewritten = String::with_capacity(content.len());
+    let mut missing = Vec::new();
+    let mut offset = 0;
+
+    for link in links {
+        if link.span.start < offset {
+            continue
+        }
+
+        let Some(symbol) = symbols.resolve(&link.target) else {
+            missing.push(link.target);
+            continue
+        };
+
+        rewritten.push_str(&content[offset..link.span.start]);
+        match link.label {
+            Some(label) => rewritten.push_str(&format!("{}({})", label, symbol.link_from(page))),
+            None => rewritten.push_str(&symbol.link_from(page)),
+        }
+        offset = link.span.end;
+    }
+
+    rewritten.push_str(&content[offset..]);
+
+    (rewritten, missing)
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::symbols::{Symbol, SymbolKind};
+
+    fn symbols() -> SymbolIndex {
+        let mut symbols = SymbolIndex::new();
+        for (name, kind, anchor) in [
+            ("renoise.app", SymbolKind::Global, "renoiseapp"),
+            ("bit.tohex", SymbolKind::Function, "tohex"),
+        ] {
+            symbols.insert(Symbol {
+                name: name.to_string(),
+                kind,
+                path: PathBuf::from("api/renoise.md"),
+                anchor: anchor.to_string(),
+            });
+        }
+
+        symbols
+    }
+
+    #[test]
+    fn rewrite_item_links() {
+        let page = Path::new("guide/intro.md");
+        let content = "See [the app](luacats:renoise.app \"app\") and [`bit.tohex`], [`bit.tohex`][] or [hex][`bit:tohex`].\n\
+            Not [`missing`] or [luacats:missing](luacats:missing), nor ``[`bit.tohex`]`` and [plain].";
+
+        let (rewritten, missing) = rewrite_links(content, page, &symbols());
+
+        assert_eq!(
+            rewritten,
+            "See [the app](../api/renoise.md#renoiseapp \"app\") and [`bit.tohex`](../api/renoise.md#tohex), \
+            [`bit.tohex`](../api/renoise.md#tohex) or [hex](../api/renoise.md#tohex).\n\
+            Not [`missing`] or [luacats:missing](luacats:missing), nor ``[`bit.tohex`]`` and [plain]."
+        );
+        assert_eq!(missing, vec!["missing", "missing"]);
+    }
+}
@@ -1,5 +1,5 @@
 //!Locations within source code files as line and character offsets.
-use std::{fmt::Display, hash::Hash, path::PathBuf};
+use std::{fmt::Display, hash::Hash, path::{Path, PathBuf}};
 
 use itertools::Itertools;
 use serde::{Deserialize, Serialize};
@@ -28,7 +28,7 @@ impl FileUri {
         }
 
         let mut stripped = self.clone();
-        stripped.0.set_path(&self.0.path().strip_prefix(base.0.path()).unwrap().to_string());
+        stripped.0.set_path(self.0.path().strip_prefix(base.0.path()).unwrap());
 
         Ok(stripped)
     }
@@ -53,7 +53,7 @@ impl FileUri {
             return (stem.to_string(), ext.to_string())
         }
 
-        return (name.clone(), String::new())
+        (name.clone(), String::new())
     }
 
     pub fn file_stem(&self) -> String {
@@ -75,18 +75,61 @@ impl FileUri {
     pub fn depth(&self) -> usize {
         let segments = self.0.path_segments().unwrap();
 
-        let mut n = 0;
-        for _ in segments {
-            n += 1
-        }
-        n = (n-1).max(0); // don't count the last segment, which is the filename
-
-        n
+        // don't count the last segment, which is the filename
+        segments.count().saturating_sub(1)
     }
 
     pub fn relative_depth(&self, base: &Self) -> usize {
         self.depth() - base.depth()
     }
+
+    /// Apply the first matching remap rule, returning the rewritten URI.
+    /// Returns `None` if no rule matches.
+    pub fn remap(&self, remaps: &[PathRemap]) -> Option<Self> {
+        let path = self.to_file_path().ok()?;
+
+        remaps
+            .iter()
+            .find_map(|remap| remap.apply(&path))
+            .and_then(|path| Self::try_from(path).ok())
+    }
+}
+
+/// A rule that rewrites a path prefix, used to map file URIs from a `doc.json`
+/// exported on another machine onto the local filesystem.
+#[derive(Clone, Debug, Eq, PartialEq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct PathRemap {
+    /// The path prefix to replace.
+    pub(crate) from: PathBuf,
+    /// The replacement path prefix.
+    pub(crate) to: PathBuf,
+}
+
+impl PathRemap {
+    pub fn new(from: impl Into<PathBuf>, to: impl Into<PathBuf>) -> Self {
+        Self {
+            from: from.into(),
+            to: to.into(),
+        }
+    }
+
+    /// The path prefix to replace.
+    pub fn from(&self) -> &Path {
+        &self.from
+    }
+
+    /// The replacement path prefix.
+    pub fn to(&self) -> &Path {
+        &self.to
+    }
+
+    /// Rewrite the path if it starts with the `from` prefix. Prefixes are
+    /// matched by whole path components.
+    pub fn apply(&self, path: &Path) -> Option<PathBuf> {
+        path.strip_prefix(&self.from)
+            .ok()
+            .map(|rest| self.to.join(rest))
+    }
 }
 
 impl TryFrom<PathBuf> for FileUri {
@@ -109,7 +152,7 @@ impl TryFrom<Url> for FileUri {
 
         let path = uri.path();
 
-        if path.len() == 0 {
+        if path.is_empty() {
             bail!("Missing file path")
         }
 
@@ -123,7 +166,7 @@ impl TryFrom<Url> for FileUri {
 
 impl Display for FileUri {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.0.to_string())
+        write!(f, "{}", self.0)
     }
 }
 
@@ -133,10 +176,10 @@ impl Display for FileUri {
 #[serde(from = "u64")]
 pub struct Position {
     /// Line position in a document (zero-based).
-    pub line: u64,
+    pub(crate) line: u64,
     /// Character offset on a line in a document (zero-based).
     /// The offset counts UTF-16 code units.
-    pub character: u64,
+    pub(crate) character: u64,
 }
 
 impl Display for Position {
@@ -146,6 +189,16 @@ impl Display for Position {
 }
 
 impl Position {
+    /// The line (zero-based).
+    pub fn line(&self) -> u64 {
+        self.line
+    }
+
+    /// The character offset on the line (zero-based), in UTF-16 code units.
+    pub fn character(&self) -> u64 {
+        self.character
+    }
+
     /// Unpack a single integer into a position using the LuaLS encoding.
     pub fn unpack(position: u64) -> Self {
         Self {
@@ -163,7 +216,7 @@ impl Position {
 
 impl From<u64> for Position {
     fn from(value: u64) -> Self {
-        Self::unpack(value.into())
+        Self::unpack(value)
     }
 }
 
@@ -206,10 +259,10 @@ impl Range {
 
     pub fn join(&self, other: &Range) -> Range {
         if other.start < self.end {
-            return self.clone()
+            return *self
         }
 
-        return Range {
+        Range {
             start: self.start,
             end: other.end,
         }
@@ -232,39 +285,30 @@ impl Range {
 #[derive(Clone, Debug, Eq, PartialEq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct Location {
     #[serde(alias = "file")]
-	pub file: FileUri,
+	pub(crate) file: FileUri,
     #[serde(flatten)]
-	pub range: Range,
+	pub(crate) range: Range,
 }
 
-impl Display for Location {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}: {}", self.file, self.range)
+impl Location {
+    pub fn file(&self) -> &FileUri {
+        &self.file
     }
-}
 
-/// A span of text from within a file.
-#[derive(Clone, Debug, Eq, PartialEq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
-pub struct Span {
-    pub text: String,
-	pub location: Location,
-}
-
-impl Span {
-    pub fn empty(location: Location) -> Self {
-        Self {
-            text: String::new(),
-            location,
-        }
+    pub fn range(&self) -> Range {
+        self.range
     }
+}
 
-    pub fn is_empty(&self) -> bool {
-        return self.text.len() == 0
+impl Display for Location {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.file, self.range)
     }
 }
 
-/// Read a range from the given text.
-pub fn read_range<'a>(text: &'a str, range: &Range) -> String {
+/// Read a range from the given text. Character offsets past the end of a
+/// line are clamped to the length of the line.
+pub fn read_range(text: &str, range: &Range) -> String {
     let (start, end) = range.bounds();
 
     text
@@ -276,18 +320,51 @@ pub fn read_range<'a>(text: &'a str, range: &Range) -> String {
         })
         .map(|(i, l)| {
             let codepoints: Vec<u16> = l.encode_utf16()
-                .into_iter()
                 .collect();
+            let clamp = |character: u64| {
+                usize::try_from(character).unwrap_or(usize::MAX).min(codepoints.len())
+            };
+
+            let from = if i == start.line { clamp(start.character) } else { 0 };
+            let to = if i == end.line { clamp(end.character) } else { codepoints.len() };
 
-            if i == start.line {
-                let start = usize::try_from(start.character).expect("oveflow");
-                String::from_utf16_lossy(&codepoints[start..])
-            } else if i == end.line {
-                let end = usize::try_from(end.character).expect("oveflow");
-                String::from_utf16_lossy(&codepoints[0..end])
-            } else {
-                l.to_string()
-            }
+            String::from_utf16_lossy(&codepoints[from..to.max(from)])
         })
-        .join("")
-}
\ No newline at end of file
+        .join("\n")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn remap_file_uri() {
+        let uri = FileUri::parse("file:///Users/matt/Code/mooncats/./testdata/test.lua").unwrap();
+        let remaps = vec![
+            PathRemap::new("/Users/matt/Code/moon", "/elsewhere"),
+            PathRemap::new("/Users/matt/Code/mooncats", "/root/crate"),
+        ];
+
+        let remapped = uri.remap(&remaps).unwrap();
+
+        assert_eq!(remapped, FileUri::parse("file:///root/crate/testdata/test.lua").unwrap());
+    }
+
+    #[test]
+    fn remap_file_uri_without_match() {
+        let uri = FileUri::parse("file:///opt/lua/basic.lua").unwrap();
+        let remaps = vec![PathRemap::new("/Users/matt", "/root")];
+
+        assert_eq!(uri.remap(&remaps), None);
+    }
+
+    #[test]
+    fn read_ranges() {
+        let text = "local t = {\n\tblack = 0,\n}";
+        let range = |start, end| Range::new(Position::unpack(start), Position::unpack(end));
+
+        assert_eq!(read_range(text, &range(10001, 10006)), "black");
+        assert_eq!(read_range(text, &range(10001, 19999)), "black = 0,");
+        assert_eq!(read_range(text, &range(6, 20001)), "t = {\n\tblack = 0,\n}");
+    }
+}
@@ -0,0 +1,769 @@
+//! A structured representation of LuaCATS types.
+//!
+//! LuaLS only exports pre-rendered `view` strings for most types, so the type
+//! tree is recovered by parsing those views.
+
+use std::fmt::{self, Display};
+
+use itertools::Itertools;
+use log::debug;
+use serde::{Deserialize, Serialize};
+
+use crate::{errors::*, json::Extends};
+
+#[derive(Clone, Debug, Eq, PartialEq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum LuaType {
+    /// A named type, such as `string`, `file*` or `renoise.Application`.
+    Name { name: String },
+    /// A generic type parameter, such as `<T>` or `<T:table>`.
+    GenericParam {
+        name: String,
+        constraint: Option<Box<LuaType>>,
+    },
+    /// A generic type applied to type arguments, such as `Set<string>`.
+    Generic { name: String, args: Vec<LuaType> },
+    /// A string, number or boolean literal, as written in the source.
+    Literal { value: String },
+    /// One of several types, such as `string|number`.
+    Union { types: Vec<LuaType> },
+    /// A type that may be nil, such as `string?`.
+    Optional { inner: Box<LuaType> },
+    /// An array of a type, such as `string[]`.
+    Array { element: Box<LuaType> },
+    /// A table with typed keys and values, such as `table<string, integer>`.
+    Table {
+        key: Box<LuaType>,
+        value: Box<LuaType>,
+    },
+    /// A function signature, such as `fun(x: integer):string`.
+    Function {
+        params: Vec<FunctionParam>,
+        returns: Vec<LuaType>,
+    },
+    /// A table literal type, such as `{ x: number, y: number }`.
+    Object { fields: Vec<ObjectField> },
+    /// Any number of values of a type, such as `...string`.
+    Variadic { inner: Box<LuaType> },
+    /// Union members LuaLS left out of a long view, such as `...(+3)`.
+    Elided { count: u32 },
+    /// A view that could not be parsed.
+    Unknown { view: String },
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct FunctionParam {
+    pub(crate) name: Option<String>,
+    pub(crate) optional: bool,
+    #[serde(rename = "type")]
+    pub(crate) param_type: Option<LuaType>,
+}
+
+impl FunctionParam {
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    pub fn optional(&self) -> bool {
+        self.optional
+    }
+
+    /// The declared type, which is missing for untyped params.
+    pub fn param_type(&self) -> Option<&LuaType> {
+        self.param_type.as_ref()
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct ObjectField {
+    pub(crate) name: String,
+    pub(crate) optional: bool,
+    #[serde(rename = "type")]
+    pub(crate) field_type: LuaType,
+}
+
+impl ObjectField {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn optional(&self) -> bool {
+        self.optional
+    }
+
+    pub fn field_type(&self) -> &LuaType {
+        &self.field_type
+    }
+}
+
+impl LuaType {
+    pub fn name(name: impl Into<String>) -> Self {
+        Self::Name { name: name.into() }
+    }
+
+    /// Parse a LuaLS type view.
+    pub fn parse(view: &str) -> Result<Self> {
+        let mut parser = Parser::new(view);
+
+        let lua_type = parser.parse_union()?;
+        parser.skip_ws();
+        ensure!(parser.is_done(), "Unexpected {:?} in type {:?}", parser.rest(), view);
+
+        Ok(lua_type)
+    }
+
+    /// Parse a LuaLS type view, keeping the raw view if it can't be parsed.
+    pub fn from_view(view: &str) -> Self {
+        Self::parse(view).unwrap_or_else(|e| {
+            debug!("{}", e);
+            Self::Unknown { view: view.to_string() }
+        })
+    }
+
+    /// Build the type for the given extends. When LuaLS provides the members of
+    /// a union they are parsed individually.
+    pub(crate) fn from_extends(extends: &Extends) -> Self {
+        match extends.types.as_slice() {
+            [] => Self::from_view(&extends.view),
+            [single] => Self::from_view(&single.view),
+            types => Self::Union {
+                types: types.iter().map(|t| Self::from_view(&t.view)).collect(),
+            },
+        }
+    }
+
+    /// Whether the type needs parentheses when used with a postfix operator.
+    fn needs_parens(&self) -> bool {
+        matches!(self, Self::Union { .. } | Self::Function { .. })
+    }
+}
+
+impl Display for LuaType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.segments().iter().map(|s| s.text()).join(""))
+    }
+}
+
+/// A piece of a rendered type. Names are kept separate from the surrounding
+/// punctuation so they can be linked to their definitions.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum TypeSegment {
+    Text(String),
+    Name(String),
+}
+
+impl TypeSegment {
+    pub fn text(&self) -> &str {
+        match self {
+            Self::Text(text) | Self::Name(text) => text,
+        }
+    }
+}
+
+impl LuaType {
+    /// Split the rendered type into text and names.
+    pub fn segments(&self) -> Vec<TypeSegment> {
+        let mut out = Vec::new();
+        self.write_segments(&mut out);
+
+        out
+    }
+
+    fn write_segments(&self, out: &mut Vec<TypeSegment>) {
+        let text = |out: &mut Vec<TypeSegment>, text: &str| {
+            out.push(TypeSegment::Text(text.to_string()))
+        };
+        let list = |out: &mut Vec<TypeSegment>, types: &[LuaType]| {
+            for (i, t) in types.iter().enumerate() {
+                if i > 0 {
+                    text(out, ", ");
+                }
+                t.write_segments(out);
+            }
+        };
+        let postfix = |out: &mut Vec<TypeSegment>, inner: &LuaType, op: &str| {
+            if inner.needs_parens() {
+                text(out, "(");
+                inner.write_segments(out);
+                text(out, ")");
+            } else {
+                inner.write_segments(out);
+            }
+            text(out, op);
+        };
+
+        match self {
+            Self::Name { name } => out.push(TypeSegment::Name(name.clone())),
+            Self::GenericParam { name, constraint } => {
+                text(out, &format!("<{}", name));
+                if let Some(constraint) = constraint {
+                    text(out, ":");
+                    constraint.write_segments(out);
+                }
+                text(out, ">");
+            }
+            Self::Generic { name, args } => {
+                out.push(TypeSegment::Name(name.clone()));
+                text(out, "<");
+                list(out, args);
+                text(out, ">");
+            }
+            Self::Literal { value } => text(out, value),
+            Self::Union { types } => {
+                for (i, t) in types.iter().enumerate() {
+                    if i > 0 && !matches!(t, Self::Elided { .. }) {
+                        text(out, "|");
+                    }
+                    t.write_segments(out);
+                }
+            }
+            Self::Optional { inner } => postfix(out, inner, "?"),
+            Self::Array { element } => postfix(out, element, "[]"),
+            Self::Table { key, value } => {
+                text(out, "table<");
+                key.write_segments(out);
+                text(out, ", ");
+                value.write_segments(out);
+                text(out, ">");
+            }
+            Self::Function { params, returns } => {
+                text(out, "fun(");
+                for (i, param) in params.iter().enumerate() {
+                    if i > 0 {
+                        text(out, ", ");
+                    }
+                    if let Some(name) = &param.name {
+                        text(out, name);
+                        if param.optional {
+                            text(out, "?");
+                        }
+                        if param.param_type.is_some() {
+                            text(out, ": ");
+                        }
+                    }
+                    if let Some(param_type) = &param.param_type {
+                        param_type.write_segments(out);
+                    }
+                }
+                text(out, ")");
+                if !returns.is_empty() {
+                    text(out, ":");
+                    list(out, returns);
+                }
+            }
+            Self::Object { fields } => {
+                text(out, "{ ");
+                for (i, field) in fields.iter().enumerate() {
+                    if i > 0 {
+                        text(out, ", ");
+                    }
+                    text(out, &field.name);
+                    text(out, if field.optional { "?: " } else { ": " });
+                    field.field_type.write_segments(out);
+                }
+                text(out, " }");
+            }
+            Self::Variadic { inner } => {
+                text(out, "...");
+                inner.write_segments(out);
+            }
+            Self::Elided { count } => text(out, &format!("...(+{})", count)),
+            Self::Unknown { view } => text(out, view),
+        }
+    }
+}
+
+/// A recursive descent parser for LuaLS type views.
+struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+    /// How many brackets the parser is nested within. Function returns are
+    /// only comma separated at the top level, where they can't be confused
+    /// with a list of parameters or type arguments.
+    depth: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(input: &'a str) -> Self {
+        Self {
+            input,
+            pos: 0,
+            depth: 0,
+        }
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.input[self.pos..]
+    }
+
+    fn is_done(&self) -> bool {
+        self.pos >= self.input.len()
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn skip_ws(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn eat(&mut self, token: &str) -> bool {
+        self.skip_ws();
+
+        if self.rest().starts_with(token) {
+            self.pos += token.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, token: &str) -> Result<()> {
+        ensure!(
+            self.eat(token),
+            "Expected {:?} at {:?} in type {:?}",
+            token,
+            self.rest(),
+            self.input
+        );
+
+        Ok(())
+    }
+
+    fn nested<T>(&mut self, func: impl FnOnce(&mut Self) -> Result<T>) -> Result<T> {
+        self.depth += 1;
+        let result = func(self);
+        self.depth -= 1;
+
+        result
+    }
+
+    fn parse_union(&mut self) -> Result<LuaType> {
+        let mut types = vec![self.parse_postfix()?];
+
+        loop {
+            self.skip_ws();
+
+            if self.rest().starts_with("...(+") {
+                types.push(self.parse_elided()?);
+            } else if self.eat("|") {
+                types.push(self.parse_postfix()?);
+            } else {
+                break;
+            }
+        }
+
+        Ok(if types.len() == 1 {
+            types.remove(0)
+        } else {
+            LuaType::Union { types }
+        })
+    }
+
+    fn parse_elided(&mut self) -> Result<LuaType> {
+        self.expect("...(+")?;
+        let count = self.take_while(|c| c.is_ascii_digit());
+        self.expect(")")?;
+
+        Ok(LuaType::Elided { count: count.parse()? })
+    }
+
+    fn parse_postfix(&mut self) -> Result<LuaType> {
+        let mut lua_type = self.parse_primary()?;
+
+        loop {
+            if self.eat("[]") {
+                lua_type = LuaType::Array { element: Box::new(lua_type) };
+            } else if self.eat("?") {
+                lua_type = LuaType::Optional { inner: Box::new(lua_type) };
+            } else {
+                break;
+            }
+        }
+
+        Ok(lua_type)
+    }
+
+    fn parse_primary(&mut self) -> Result<LuaType> {
+        self.skip_ws();
+
+        let c = self
+            .peek()
+            .ok_or_else(|| anyhow!("Unexpected end of type {:?}", self.input))?;
+
+        match c {
+            '(' => {
+                self.expect("(")?;
+                let lua_type = self.nested(|p| p.parse_union())?;
+                self.expect(")")?;
+
+                Ok(lua_type)
+            }
+            '"' | '\'' | '`' => self.parse_string(c),
+            '{' => self.parse_object(),
+            '<' => self.parse_generic_param(),
+            '.' if self.rest().starts_with("...") => {
+                self.expect("...")?;
+
+                if self.starts_type() {
+                    let inner = self.parse_postfix()?;
+                    Ok(LuaType::Variadic { inner: Box::new(inner) })
+                } else {
+                    Ok(LuaType::name("..."))
+                }
+            }
+            c if c.is_ascii_digit() || c == '-' => {
+                let value = self.take_while(|c| c.is_ascii_alphanumeric() || c == '-' || c == '.');
+                Ok(LuaType::Literal { value })
+            }
+            c if is_ident_start(c) => {
+                let name = self.parse_ident();
+
+                match name.as_str() {
+                    "fun" if self.rest().starts_with('(') => self.parse_function(),
+                    "true" | "false" => Ok(LuaType::Literal { value: name }),
+                    _ if self.rest().starts_with('<') => self.parse_generic(name),
+                    _ => Ok(LuaType::Name { name }),
+                }
+            }
+            _ => bail!("Unexpected {:?} in type {:?}", self.rest(), self.input),
+        }
+    }
+
+    fn starts_type(&mut self) -> bool {
+        self.skip_ws();
+
+        matches!(self.peek(), Some(c) if is_ident_start(c) || "(\"'`{<-".contains(c) || c.is_ascii_digit())
+    }
+
+    fn take_while(&mut self, func: impl Fn(char) -> bool) -> String {
+        let rest = self.rest();
+        let len = rest.find(|c| !func(c)).unwrap_or(rest.len());
+        self.pos += len;
+
+        rest[..len].to_string()
+    }
+
+    fn parse_ident(&mut self) -> String {
+        let rest = self.rest();
+        let len = rest
+            .char_indices()
+            .find(|&(i, c)| {
+                !(c.is_alphanumeric() || "_.*-".contains(c)) || rest[i..].starts_with("..")
+            })
+            .map(|(i, _)| i)
+            .unwrap_or(rest.len());
+        self.pos += len;
+
+        rest[..len].to_string()
+    }
+
+    fn parse_string(&mut self, quote: char) -> Result<LuaType> {
+        let rest = self.rest();
+        let mut escaped = false;
+        let end = rest
+            .char_indices()
+            .skip(1)
+            .find(|&(_, c)| {
+                let found = c == quote && !escaped;
+                escaped = c == '\\' && !escaped;
+                found
+            })
+            .map(|(i, _)| i)
+            .ok_or_else(|| anyhow!("Unterminated string in type {:?}", self.input))?;
+        self.pos += end + quote.len_utf8();
+
+        Ok(LuaType::Literal { value: rest[..end + quote.len_utf8()].to_string() })
+    }
+
+    fn parse_generic_param(&mut self) -> Result<LuaType> {
+        self.expect("<")?;
+        let name = self.parse_ident();
+        ensure!(!name.is_empty(), "Missing generic name in type {:?}", self.input);
+
+        let constraint = if self.eat(":") {
+            Some(Box::new(self.nested(|p| p.parse_union())?))
+        } else {
+            None
+        };
+        self.expect(">")?;
+
+        Ok(LuaType::GenericParam { name, constraint })
+    }
+
+    fn parse_generic(&mut self, name: String) -> Result<LuaType> {
+        self.expect("<")?;
+        let args = self.nested(|p| p.parse_list(">"))?;
+
+        Ok(match (name.as_str(), args.as_slice()) {
+            ("table", [key, value]) => LuaType::Table {
+                key: Box::new(key.clone()),
+                value: Box::new(value.clone()),
+            },
+            _ => LuaType::Generic { name, args },
+        })
+    }
+
+    /// Parse a comma separated list of types up to and including the closing
+    /// token.
+    fn parse_list(&mut self, close: &str) -> Result<Vec<LuaType>> {
+        let mut types = Vec::new();
+
+        while !self.eat(close) {
+            if !types.is_empty() {
+                self.expect(",")?;
+            }
+            types.push(self.parse_union()?);
+        }
+
+        Ok(types)
+    }
+
+    fn parse_function(&mut self) -> Result<LuaType> {
+        self.expect("(")?;
+
+        let params = self.nested(|p| {
+            let mut params = Vec::new();
+
+            while !p.eat(")") {
+                if !params.is_empty() {
+                    p.expect(",")?;
+                }
+                params.push(p.parse_param()?);
+            }
+
+            Ok(params)
+        })?;
+
+        let mut returns = Vec::new();
+        if self.eat(":") {
+            returns.push(self.parse_union()?);
+
+            while self.depth == 0 && self.eat(",") {
+                returns.push(self.parse_union()?);
+            }
+        }
+
+        Ok(LuaType::Function { params, returns })
+    }
+
+    fn parse_param(&mut self) -> Result<FunctionParam> {
+        self.skip_ws();
+
+        // Varargs may be named ("...: string") or not ("...string")
+        if self.rest().starts_with("...") {
+            let after = self.rest()[3..].trim_start();
+            if !after.starts_with(':') {
+                return Ok(FunctionParam {
+                    name: None,
+                    optional: false,
+                    param_type: Some(self.parse_postfix()?),
+                });
+            }
+            self.expect("...")?;
+
+            return Ok(FunctionParam {
+                name: Some("...".to_string()),
+                optional: false,
+                param_type: self.parse_param_type()?,
+            });
+        }
+
+        let name = self.parse_ident();
+        ensure!(!name.is_empty(), "Expected parameter at {:?} in type {:?}", self.rest(), self.input);
+        let optional = self.eat("?");
+
+        Ok(FunctionParam {
+            name: Some(name),
+            optional,
+            param_type: self.parse_param_type()?,
+        })
+    }
+
+    fn parse_param_type(&mut self) -> Result<Option<LuaType>> {
+        if self.eat(":") {
+            Ok(Some(self.parse_union()?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn parse_object(&mut self) -> Result<LuaType> {
+        self.expect("{")?;
+
+        let fields = self.nested(|p| {
+            let mut fields = Vec::new();
+
+            while !p.eat("}") {
+                if !fields.is_empty() {
+                    p.expect(",")?;
+                }
+
+                p.skip_ws();
+                let name = if p.rest().starts_with('[') {
+                    let end = p.rest()
+                        .find(']')
+                        .ok_or_else(|| anyhow!("Unterminated field key in type {:?}", p.input))?;
+                    let name = p.rest()[..=end].to_string();
+                    p.pos += end + 1;
+                    name
+                } else {
+                    p.parse_ident()
+                };
+                ensure!(!name.is_empty(), "Expected field at {:?} in type {:?}", p.rest(), p.input);
+                let optional = p.eat("?");
+                p.expect(":")?;
+
+                fields.push(ObjectField {
+                    name,
+                    optional,
+                    field_type: p.parse_union()?,
+                });
+            }
+
+            Ok(fields)
+        })?;
+
+        Ok(LuaType::Object { fields })
+    }
+}
+
+fn is_ident_start(c: char) -> bool {
+    c.is_alphabetic() || c == '_'
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::json::Definition;
+
+    fn name(name: &str) -> LuaType {
+        LuaType::name(name)
+    }
+
+    #[test]
+    fn parse_names() {
+        assert_eq!(LuaType::parse("string").unwrap(), name("string"));
+        assert_eq!(LuaType::parse("renoise.Application").unwrap(), name("renoise.Application"));
+        assert_eq!(LuaType::parse("file*").unwrap(), name("file*"));
+    }
+
+    #[test]
+    fn parse_unions_and_optionals() {
+        assert_eq!(
+            LuaType::parse("(string|number)?").unwrap(),
+            LuaType::Optional {
+                inner: Box::new(LuaType::Union { types: vec![name("string"), name("number")] }),
+            }
+        );
+        assert_eq!(
+            LuaType::parse("\"a\"|\"r\"...(+7)").unwrap(),
+            LuaType::Union {
+                types: vec![
+                    LuaType::Literal { value: "\"a\"".to_string() },
+                    LuaType::Literal { value: "\"r\"".to_string() },
+                    LuaType::Elided { count: 7 },
+                ],
+            }
+        );
+    }
+
+    #[test]
+    fn parse_tables_and_generics() {
+        assert_eq!(
+            LuaType::parse("table<<K>, <V>>").unwrap(),
+            LuaType::Table {
+                key: Box::new(LuaType::GenericParam { name: "K".to_string(), constraint: None }),
+                value: Box::new(LuaType::GenericParam { name: "V".to_string(), constraint: None }),
+            }
+        );
+        assert_eq!(
+            LuaType::parse("<T:table>").unwrap(),
+            LuaType::GenericParam { name: "T".to_string(), constraint: Some(Box::new(name("table"))) }
+        );
+        assert_eq!(
+            LuaType::parse("Set<string>[]").unwrap(),
+            LuaType::Array {
+                element: Box::new(LuaType::Generic { name: "Set".to_string(), args: vec![name("string")] }),
+            }
+        );
+    }
+
+    #[test]
+    fn parse_functions() {
+        assert_eq!(
+            LuaType::parse("fun(s: string, p?: integer):integer, integer").unwrap(),
+            LuaType::Function {
+                params: vec![
+                    FunctionParam { name: Some("s".to_string()), optional: false, param_type: Some(name("string")) },
+                    FunctionParam { name: Some("p".to_string()), optional: true, param_type: Some(name("integer")) },
+                ],
+                returns: vec![name("integer"), name("integer")],
+            }
+        );
+        assert_eq!(
+            LuaType::parse("fun(...any):...unknown").unwrap(),
+            LuaType::Function {
+                params: vec![FunctionParam {
+                    name: None,
+                    optional: false,
+                    param_type: Some(LuaType::Variadic { inner: Box::new(name("any")) }),
+                }],
+                returns: vec![LuaType::Variadic { inner: Box::new(name("unknown")) }],
+            }
+        );
+    }
+
+    #[test]
+    fn parse_objects() {
+        assert_eq!(
+            LuaType::parse("{ x: number, y?: number }").unwrap(),
+            LuaType::Object {
+                fields: vec![
+                    ObjectField { name: "x".to_string(), optional: false, field_type: name("number") },
+                    ObjectField { name: "y".to_string(), optional: true, field_type: name("number") },
+                ],
+            }
+        );
+    }
+
+    #[test]
+    fn parse_invalid() {
+        assert!(LuaType::parse("table<string").is_err());
+        assert_eq!(LuaType::from_view("a b"), LuaType::Unknown { view: "a b".to_string() });
+    }
+
+    /// Every view LuaLS generated for the test data should parse and display
+    /// the same way.
+    #[test]
+    fn round_trip_views() {
+        let docs: Vec<Definition> = serde_json::from_str(include_str!("../testdata/doc.json")).unwrap();
+
+        let views: Vec<&String> = docs
+            .iter()
+            .flat_map(|doc| {
+                doc.defines
+                    .iter()
+                    .flat_map(|define| define.extends.iter())
+                    .chain(doc.fields.iter().map(|field| &field.extends))
+            })
+            .flat_map(|extends| {
+                extends
+                    .args
+                    .iter()
+                    .map(|arg| &arg.view)
+                    .chain(extends.returns.iter().map(|ret| &ret.view))
+            })
+            .collect();
+
+        assert!(!views.is_empty());
+
+        for view in views {
+            let lua_type = LuaType::parse(view).unwrap();
+            assert_eq!(&lua_type.to_string(), view);
+        }
+    }
+}
@@ -1,28 +1,57 @@
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
 use crate::errors::*;
-use handlebars::{no_escape, Handlebars};
+use itertools::Itertools;
+use log::*;
+use pulldown_cmark::{Event, Parser, Tag, TagEnd};
+use handlebars::{
+    handlebars_helper, no_escape, Context, Handlebars, Helper, HelperDef, HelperResult, Output, RenderContext,
+    RenderErrorReason,
+};
 use rust_embed::Embed;
 use serde::{Deserialize, Serialize};
 
-use crate::doctree::{DocItem, DocItemEnum, MetaFile};
+use crate::{
+    doctree::{DocItem, DocItemEnum, ItemRef, MetaFile, PageGranularity},
+    index::IndexEntry,
+    lua_type::{LuaType, TypeSegment},
+    symbols::SymbolIndex,
+};
 
 #[derive(Embed)]
 #[folder = "templates"]
 #[include = "*.hbs"]
 struct Assets;
 
+/// Renders files and items as markdown pages.
 pub struct MarkdownRenderer<'a> {
     hbs: Handlebars<'a>,
+    symbols: Arc<SymbolIndex>,
+    granularity: PageGranularity,
 }
 
 
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 struct TemplateData {
     pub name: String,
+    /// The chapter path, used to build relative links.
+    pub path: String,
     pub classes: Vec<DocItem>,
     pub tables: Vec<DocItem>,
     pub type_aliases: Vec<DocItem>,
     pub enums: Vec<DocItem>,
     pub globals: Vec<DocItem>,
+    /// Link to the pages of classes, tables and enums instead of listing them.
+    pub item_pages: bool,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+struct IndexData<'a> {
+    title: &'a str,
+    path: String,
+    entries: &'a [IndexEntry],
 }
 
 impl From<&MetaFile> for TemplateData {
@@ -63,31 +92,610 @@ impl From<&MetaFile> for TemplateData {
 
         Self {
             name,
+            path: String::new(),
             classes,
             tables,
             type_aliases,
             enums,
             globals,
+            item_pages: false,
         }
     }
 }
 
 impl<'a> MarkdownRenderer<'a> {
-    pub fn new() -> Self {
+    /// Create a renderer that links type names to the given symbols.
+    pub fn with_symbols(symbols: Arc<SymbolIndex>) -> Self {
         let mut hbs = Handlebars::new();
 
         hbs.set_strict_mode(true);
         hbs.register_embed_templates_with_extension::<Assets>(".hbs").expect("invalid templates");
         hbs.register_escape_fn(no_escape);
+        hbs.register_helper("luaType", Box::new(LuaTypeHelper));
+        hbs.register_helper("luaTypeLink", Box::new(LuaTypeLinkHelper { symbols: symbols.clone() }));
+        hbs.register_helper("tableCell", Box::new(table_cell));
+        hbs.register_helper("anchor", Box::new(anchor));
+        hbs.register_helper("badges", Box::new(badges));
+        hbs.register_helper("firstSentence", Box::new(first_sentence_helper));
+        hbs.register_helper("symbolLink", Box::new(SymbolLinkHelper { symbols: symbols.clone() }));
 
         Self {
-            hbs
+            hbs,
+            symbols,
+            granularity: PageGranularity::File,
+        }
+    }
+
+    /// Render files as an index of their class, table and enum pages when the
+    /// granularity is [PageGranularity::Item]. Use the same granularity as the
+    /// symbol index.
+    pub fn set_page_granularity(&mut self, granularity: PageGranularity) {
+        self.granularity = granularity;
+    }
+
+    /// Register every `.hbs` file in the directory as a template named after
+    /// the file, replacing the built-in template of the same name.
+    pub fn load_templates(&mut self, dir: &Path) -> Result<()> {
+        let entries = fs::read_dir(dir)
+            .map_err(|e| anyhow!("Unable to read template directory {}: {}", dir.display(), e))?;
+        let paths: Vec<_> = entries
+            .map(|entry| entry.map(|entry| entry.path()))
+            .filter_ok(|path| path.extension().is_some_and(|ext| ext == "hbs"))
+            .try_collect()?;
+
+        for path in paths.into_iter().sorted() {
+            let Some(name) = path.file_stem().and_then(|name| name.to_str()) else {
+                continue
+            };
+
+            if self.hbs.has_template(name) {
+                debug!("Overriding template {:?} with {}", name, path.display());
+            } else {
+                debug!("Adding template {:?} from {}", name, path.display());
+            }
+
+            self.hbs
+                .register_template_file(name, &path)
+                .map_err(|e| anyhow!("Invalid template {}: {}", path.display(), e))?;
         }
+
+        Ok(())
     }
 
     pub fn render_meta(&self, meta_file: &MetaFile) -> Result<String> {
-        let data: TemplateData = meta_file.into();
+        let mut data: TemplateData = meta_file.into();
+        data.item_pages = self.granularity == PageGranularity::Item;
+        if let Some(path) = self.symbols.page(&meta_file.uri) {
+            data.path = path.to_string_lossy().to_string();
+        }
+
+        self.hbs
+            .render("meta_file", &data)
+            .map_err(|e| anyhow!("Unable to render {}: {}", meta_file.uri, e))
+    }
+
+    /// Render an index chapter at the given path.
+    pub(crate) fn render_index(&self, title: &str, entries: &[IndexEntry], page: &Path) -> Result<String> {
+        let data = IndexData {
+            title,
+            path: page.to_string_lossy().to_string(),
+            entries,
+        };
+
+        self.hbs
+            .render("index", &data)
+            .map_err(|e| anyhow!("Unable to render {}: {}", title, e))
+    }
+
+    /// Render a single item or member for a chapter at the given path.
+    pub fn render_item(&self, item: ItemRef, page: &Path) -> Result<String> {
+        let (template, mut data) = match item {
+            ItemRef::Item(item) => {
+                let template = match item.inner {
+                    DocItemEnum::Class(_) => "class",
+                    DocItemEnum::Table(_) => "table",
+                    DocItemEnum::TypeAlias(_) => "type_alias",
+                    DocItemEnum::Enum(_) => "enum",
+                    DocItemEnum::Global(_) => "global",
+                };
+                (template, serde_json::to_value(item)?)
+            },
+            ItemRef::Function(parent, function) => {
+                let mut data = serde_json::to_value(function)?;
+                data["parent"] = parent.name.clone().into();
+                ("named_function", data)
+            },
+            ItemRef::Field(parent, field) => {
+                let mut data = serde_json::to_value(field)?;
+                data["parent"] = parent.name.clone().into();
+                ("field", data)
+            },
+        };
+
+        data["path"] = page.to_string_lossy().into();
+
+        self.hbs
+            .render(template, &data)
+            .map_err(|e| anyhow!("Unable to render {}: {}", item.path(), e))
+    }
+}
+
+fn lua_type_param(h: &Helper<'_>, name: &'static str) -> Result<LuaType, RenderErrorReason> {
+    let param = h
+        .param(0)
+        .ok_or(RenderErrorReason::ParamNotFoundForIndex(name, 0))?;
+
+    serde_json::from_value(param.value().clone())
+        .map_err(|e| RenderErrorReason::Other(format!("Invalid type {}: {}", param.value(), e)))
+}
+
+/// Renders a serialized [LuaType] as a LuaCATS type expression.
+struct LuaTypeHelper;
+
+impl HelperDef for LuaTypeHelper {
+    fn call<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'rc>,
+        _: &'reg Handlebars<'reg>,
+        _: &'rc Context,
+        _: &mut RenderContext<'reg, 'rc>,
+        out: &mut dyn Output,
+    ) -> HelperResult {
+        let lua_type = lua_type_param(h, "luaType")?;
+
+        out.write(&lua_type.to_string())?;
+
+        Ok(())
+    }
+}
 
-        Ok(self.hbs.render("meta_file", &data)?)
+/// Renders a serialized [LuaType] as inline markdown code, linking every
+/// type name that has a symbol to its definition.
+/// With `cell=true`, pipes are escaped so the type fits in a table cell.
+struct LuaTypeLinkHelper {
+    symbols: Arc<SymbolIndex>,
+}
+
+impl HelperDef for LuaTypeLinkHelper {
+    fn call<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'rc>,
+        _: &'reg Handlebars<'reg>,
+        ctx: &'rc Context,
+        _: &mut RenderContext<'reg, 'rc>,
+        out: &mut dyn Output,
+    ) -> HelperResult {
+        let lua_type = lua_type_param(h, "luaTypeLink")?;
+        let links = render_type_links(&lua_type, &self.symbols, page_path(ctx));
+
+        // Escape pipes when rendering into a table cell
+        if h.hash_get("cell").is_some_and(|cell| cell.value().as_bool() == Some(true)) {
+            out.write(&links.replace('|', "\\|"))?;
+        } else {
+            out.write(&links)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Renders a symbol name as inline markdown code, linked to its definition
+/// when it has one. Given an item and a member name, it links to the member
+/// and shows the member name.
+struct SymbolLinkHelper {
+    symbols: Arc<SymbolIndex>,
+}
+
+impl HelperDef for SymbolLinkHelper {
+    fn call<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'rc>,
+        _: &'reg Handlebars<'reg>,
+        ctx: &'rc Context,
+        _: &mut RenderContext<'reg, 'rc>,
+        out: &mut dyn Output,
+    ) -> HelperResult {
+        let name = h
+            .param(0)
+            .and_then(|param| param.value().as_str())
+            .ok_or(RenderErrorReason::ParamNotFoundForIndex("symbolLink", 0))?;
+
+        if let Some(member) = h.param(1).and_then(|param| param.value().as_str()) {
+            let path = format!("{}.{}", name, member);
+            let link = match self.symbols.get(&path) {
+                Some(symbol) => format!("[{}]({})", code_span(member), symbol.link_from(page_path(ctx))),
+                None => code_span(member),
+            };
+            out.write(&link)?;
+
+            return Ok(())
+        }
+
+        let lua_type = LuaType::name(name);
+        out.write(&render_type_links(&lua_type, &self.symbols, page_path(ctx)))?;
+
+        Ok(())
+    }
+}
+
+// Collapse text onto a single line so it fits in a markdown table cell.
+handlebars_helper!(table_cell: |text: Json| {
+    text.as_str()
+        .unwrap_or_default()
+        .split_whitespace()
+        .join(" ")
+        .replace('|', "\\|")
+});
+
+// The first sentence of a markdown description.
+handlebars_helper!(first_sentence_helper: |text: Json| {
+    first_sentence(text.as_str().unwrap_or_default())
+});
+
+// An anchor for an item or member, made from the item name and member name.
+// An HTML anchor rather than a heading id, so it can also go in table rows.
+handlebars_helper!(anchor: |*args| {
+    format!("<a id=\"{}\"></a>", args.iter().filter_map(|arg| arg.as_str()).join("."))
+});
+
+// Badges for a member that isn't public, and for async and nodiscard
+// functions.
+handlebars_helper!(badges: |member: Json| {
+    let visibility = member["visibility"].as_str().filter(|visibility| *visibility != "public");
+    let flags = [("is_async", "async"), ("nodiscard", "nodiscard")]
+        .into_iter()
+        .filter(|(key, _)| member[*key].as_bool().unwrap_or(false))
+        .map(|(_, badge)| badge);
+
+    visibility.into_iter().chain(flags).map(|badge| format!("<kbd>{}</kbd> ", badge)).join("")
+});
+
+/// The first sentence of the first paragraph of a markdown description, with
+/// its markdown intact. A sentence ends at a `.`, `!` or `?` in plain text that
+/// is followed by the end of the paragraph, or by a space and a word that
+/// doesn't start with a lowercase letter, so `e.g. this` doesn't end one.
+/// Returns an empty string when the description doesn't start with a
+/// paragraph.
+fn first_sentence(text: &str) -> String {
+    let mut parser = Parser::new(text).into_offset_iter();
+
+    let Some((Event::Start(Tag::Paragraph), paragraph)) = parser.next() else {
+        return String::new()
+    };
+
+    let mut depth = 0;
+    let mut end = paragraph.end;
+    for (event, span) in parser {
+        match event {
+            Event::Start(_) => depth += 1,
+            Event::End(TagEnd::Paragraph) if depth == 0 => break,
+            Event::End(_) => depth -= 1,
+            Event::Text(_) if depth == 0 => {
+                if let Some(sentence_end) = find_sentence_end(text, span, paragraph.end) {
+                    end = sentence_end;
+                    break
+                }
+            },
+            _ => {},
+        }
     }
-}
\ No newline at end of file
+
+    text[paragraph.start..end].split_whitespace().join(" ")
+}
+
+/// The end of the first sentence ending in the span of plain text.
+fn find_sentence_end(text: &str, span: std::ops::Range<usize>, paragraph_end: usize) -> Option<usize> {
+    text[span.clone()]
+        .match_indices(['.', '!', '?'])
+        .map(|(index, _)| span.start + index + 1)
+        .find(|&end| {
+            let rest = &text[end..paragraph_end];
+            let next = rest.trim_start();
+
+            next.is_empty() || (rest.len() > next.len() && !next.starts_with(char::is_lowercase))
+        })
+}
+
+/// The path of the chapter being rendered.
+fn page_path(ctx: &Context) -> &Path {
+    let page = ctx
+        .data()
+        .get("path")
+        .and_then(|path| path.as_str())
+        .unwrap_or_default();
+
+    Path::new(page)
+}
+
+/// Render a type as inline code, with links for known names.
+fn render_type_links(lua_type: &LuaType, symbols: &SymbolIndex, page: &Path) -> String {
+    let mut markdown = String::new();
+    let mut text = String::new();
+
+    for segment in lua_type.segments() {
+        let symbol = match &segment {
+            TypeSegment::Name(name) => symbols.get(name),
+            TypeSegment::Text(_) => None,
+        };
+
+        match symbol {
+            Some(symbol) => {
+                if !text.is_empty() {
+                    markdown.push_str(&code_span(&text));
+                    text.clear();
+                }
+                markdown.push_str(&format!("[{}]({})", code_span(segment.text()), symbol.link_from(page)));
+            }
+            None => text.push_str(segment.text()),
+        }
+    }
+
+    if !text.is_empty() {
+        markdown.push_str(&code_span(&text));
+    }
+
+    markdown
+}
+
+/// Wrap text in a markdown code span, using a longer fence when the text
+/// contains backticks.
+fn code_span(text: &str) -> String {
+    if text.contains('`') {
+        format!("`` {} ``", text)
+    } else {
+        format!("`{}`", text)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::{
+        doctree::{
+            Class, Deprecation, Enum, EnumMember, Field, Function, Global, InheritedMembers, NamedFunction, Table, TypeAlias,
+            Visibility,
+        },
+        location::FileUri,
+        symbols::{Symbol, SymbolKind},
+        testing::{function, item, range},
+    };
+
+    #[test]
+    fn render_linked_types() {
+        let mut symbols = SymbolIndex::new();
+        symbols.insert(Symbol {
+            name: "renoise.Song".to_string(),
+            kind: SymbolKind::Class,
+            path: PathBuf::from("renoise/song.md"),
+            anchor: "renoisesong".to_string(),
+        });
+        let page = Path::new("renoise.md");
+
+        let render = |view: &str| render_type_links(&LuaType::parse(view).unwrap(), &symbols, page);
+
+        assert_eq!(render("string"), "`string`");
+        assert_eq!(render("renoise.Song"), "[`renoise.Song`](renoise/song.md#renoisesong)");
+        assert_eq!(
+            render("table<string, renoise.Song>|nil"),
+            "`table<string, `[`renoise.Song`](renoise/song.md#renoisesong)`>|nil`"
+        );
+        assert_eq!(render("\"a\"|`b`"), "`` \"a\"|`b` ``");
+    }
+
+    #[test]
+    fn render_class_parents() -> Result<()> {
+        let mut file = MetaFile::new(FileUri::parse("file:///library/shapes.lua")?);
+        file.add_item(item("Rect", DocItemEnum::Class(Class {
+            parents: vec![LuaType::name("Shape")],
+            inherited: vec![InheritedMembers {
+                class: "Shape".to_string(),
+                fields: vec![Field::new("id".to_string(), None, LuaType::name("integer"), range(0))],
+                methods: vec![NamedFunction::new("area".to_string(), function("Shape:area()"))],
+            }],
+            ..Class::default()
+        })));
+        let mut symbols = SymbolIndex::new();
+        symbols.insert(Symbol {
+            name: "Shape".to_string(),
+            kind: SymbolKind::Class,
+            path: PathBuf::from("shapes.md"),
+            anchor: "shape".to_string(),
+        });
+        for (member, kind) in [("id", SymbolKind::Field), ("area", SymbolKind::Function)] {
+            symbols.insert(Symbol {
+                name: format!("Shape.{}", member),
+                kind,
+                path: PathBuf::from("shapes.md"),
+                anchor: format!("Shape.{}", member),
+            });
+        }
+
+        let md = MarkdownRenderer::with_symbols(Arc::new(symbols)).render_meta(&file)?;
+
+        assert!(md.contains("Inherits from: [`Shape`](shapes.md#shape)"), "{}", md);
+        assert!(md.contains("#### Inherited from [`Shape`](shapes.md#shape)"), "{}", md);
+        assert!(md.contains("- [`id`](shapes.md#Shape.id) `integer`"), "{}", md);
+        assert!(md.contains("- [`area`](shapes.md#Shape.area)()"), "{}", md);
+
+        Ok(())
+    }
+
+    #[test]
+    fn render_enum_members() -> Result<()> {
+        let mut lua_enum = Enum::default();
+        lua_enum.add_field(EnumMember::new(
+            "black".to_string(),
+            Some("The\ncolor | black".to_string()),
+            Some("0".to_string()),
+            range(0),
+        ));
+        let mut file = MetaFile::new(FileUri::parse("file:///library/colors.lua")?);
+        file.add_item(item("colors", DocItemEnum::Enum(lua_enum)));
+
+        let md = MarkdownRenderer::with_symbols(Arc::default()).render_meta(&file)?;
+
+        assert!(md.contains("Type: `integer`"), "{}", md);
+        assert!(md.contains("##### colors <a id=\"colors\"></a>"), "{}", md);
+        assert!(md.contains("| <a id=\"colors.black\"></a>`black` | `0` | The color \\| black |"), "{}", md);
+
+        Ok(())
+    }
+
+    #[test]
+    fn render_anchors() -> Result<()> {
+        let mut table = Table::default();
+        table.add_field(Field::new("version".to_string(), None, LuaType::name("string"), range(0)));
+        table.add_function(NamedFunction::new("open".to_string(), function("function app.open()")));
+        let mut lua_enum = Enum::default();
+        lua_enum.add_field(EnumMember::new("black".to_string(), None, Some("0".to_string()), range(0)));
+        let mut file = MetaFile::new(FileUri::parse("file:///library/app.lua")?);
+        file.add_item(item("Song", DocItemEnum::Class(Class {
+            fields: vec![Field::new("name".to_string(), None, LuaType::name("string"), range(0))],
+            methods: vec![NamedFunction::new("save".to_string(), function("Song:save()"))],
+            ..Class::default()
+        })));
+        file.add_item(item("app", DocItemEnum::Table(table)));
+        file.add_item(item("SongId", DocItemEnum::TypeAlias(TypeAlias { aliased_type: LuaType::name("integer") })));
+        file.add_item(item("colors", DocItemEnum::Enum(lua_enum)));
+        file.add_item(item("greet", DocItemEnum::Global(Global::Function(function("function greet()")))));
+
+        let md = MarkdownRenderer::with_symbols(Arc::default()).render_meta(&file)?;
+
+        for heading in [
+            "### Song <a id=\"Song\"></a>\n",
+            "##### name <a id=\"Song.name\"></a>\n",
+            "##### save <a id=\"Song.save\"></a>\n",
+            "### app <a id=\"app\"></a>\n",
+            "##### version <a id=\"app.version\"></a>\n",
+            "##### open <a id=\"app.open\"></a>\n",
+            "##### `SongId` <a id=\"SongId\"></a>\n",
+            "##### colors <a id=\"colors\"></a>\n",
+            "| <a id=\"colors.black\"></a>`black` |",
+            "### greet <a id=\"greet\"></a>\n",
+        ] {
+            assert!(md.contains(heading), "{:?} in {}", heading, md);
+        }
+        assert!(!md.contains("{#"), "{}", md);
+
+        Ok(())
+    }
+
+    #[test]
+    fn render_function_overloads() -> Result<()> {
+        let mut greet = function("function greet(name: string)");
+        greet.add_overload(function("function greet()"));
+        let mut file = MetaFile::new(FileUri::parse("file:///library/hello.lua")?);
+        file.add_item(item("greet", DocItemEnum::Global(Global::Function(greet))));
+
+        let md = MarkdownRenderer::with_symbols(Arc::default()).render_meta(&file)?;
+
+        assert!(md.contains("function greet(name: string)"), "{}", md);
+        assert!(md.contains("Overloads:"), "{}", md);
+        assert!(md.contains("function greet()"), "{}", md);
+
+        Ok(())
+    }
+
+    #[test]
+    fn override_templates() -> Result<()> {
+        let dir = tempdir::TempDir::new("mooncats-templates")?;
+        fs::write(dir.path().join("class.hbs"), "Class {{name}} {{> field_list}}\n")?;
+        fs::write(dir.path().join("field_list.hbs"), "with {{len fields}} fields")?;
+        fs::write(dir.path().join("notes.txt"), "{{#if}}")?;
+
+        let mut file = MetaFile::new(FileUri::parse("file:///library/shapes.lua")?);
+        file.add_item(item("Shape", DocItemEnum::Class(Class::default())));
+
+        let mut md = MarkdownRenderer::with_symbols(Arc::new(SymbolIndex::new()));
+        md.load_templates(dir.path())?;
+        let page = md.render_meta(&file)?;
+
+        assert!(page.contains("Class Shape with 0 fields\n"), "{}", page);
+        assert!(page.starts_with("## Shapes"), "{}", page);
+
+        fs::write(dir.path().join("enum.hbs"), "{{#each fields}}")?;
+        let error = md.load_templates(dir.path()).unwrap_err().to_string();
+        assert!(error.starts_with(&format!("Invalid template {}", dir.path().join("enum.hbs").display())), "{}", error);
+
+        fs::remove_file(dir.path().join("enum.hbs"))?;
+        fs::write(dir.path().join("class.hbs"), "{{missing}}")?;
+        md.load_templates(dir.path())?;
+        let error = md.render_meta(&file).unwrap_err().to_string();
+        assert!(error.starts_with("Unable to render file:///library/shapes.lua"), "{}", error);
+        assert!(error.contains("\"class\""), "{}", error);
+
+        Ok(())
+    }
+
+    #[test]
+    fn extract_first_sentence() {
+        assert_eq!(first_sentence("Opens the song. Fails if it's missing."), "Opens the song.");
+        assert_eq!(first_sentence("Is it open? Check\nthis first!"), "Is it open?");
+        assert_eq!(first_sentence("Uses `a.b. C` and [v1. Two](https://x.org/a.B) e.g. here. Then"), "Uses `a.b. C` and [v1. Two](https://x.org/a.B) e.g. here.");
+        assert_eq!(first_sentence("Spans *two\nlines.* Still going\n\nNext paragraph."), "Spans *two lines.* Still going");
+        assert_eq!(first_sentence("Version 2.0 is out. Yay"), "Version 2.0 is out.");
+        assert_eq!(first_sentence("- a list. Item"), "");
+        assert_eq!(first_sentence(""), "");
+    }
+
+    #[test]
+    fn render_member_summary() -> Result<()> {
+        let mut file = MetaFile::new(FileUri::parse("file:///library/song.lua")?);
+        file.add_item(DocItem {
+            description: Some("A song. With patterns.".to_string()),
+            ..item("Song", DocItemEnum::Class(Class {
+                fields: vec![Field::new(
+                    "name".to_string(),
+                    Some("The song name. Can't be empty.".to_string()),
+                    LuaType::parse("string|nil")?,
+                    range(0),
+                )],
+                methods: vec![NamedFunction::new(
+                    "save".to_string(),
+                    Function {
+                        description: Some("Save the song, e.g. before closing. Overwrites it.".to_string()),
+                        ..function("Song:save()")
+                    },
+                )],
+                ..Class::default()
+            }))
+        });
+
+        let md = MarkdownRenderer::with_symbols(Arc::new(SymbolIndex::new())).render_meta(&file)?;
+
+        assert!(md.contains("| `Song` | class | A song. |\n"), "{}", md);
+        assert!(md.contains("| [`name`](#Song.name) | `string\\|nil` | The song name. |\n"), "{}", md);
+        assert!(md.contains("| [`save()`](#Song.save) | `Song:save()` | Save the song, e.g. before closing. |\n"), "{}", md);
+
+        Ok(())
+    }
+
+    #[test]
+    fn render_badges() -> Result<()> {
+        let mut field = Field::new("id".to_string(), None, LuaType::name("integer"), range(0));
+        field.visibility = Visibility::Private;
+        field.deprecated = Some(Deprecation::default());
+        let mut load = NamedFunction::new("load".to_string(), Function {
+            is_async: true,
+            nodiscard: true,
+            ..function("Song:load()")
+        });
+        load.deprecated = Some(Deprecation { message: Some("Use `Song:open` instead.".to_string()) });
+        let mut file = MetaFile::new(FileUri::parse("file:///library/song.lua")?);
+        file.add_item(item("Song", DocItemEnum::Class(Class {
+            fields: vec![field],
+            methods: vec![load],
+            ..Class::default()
+        })));
+
+        let md = MarkdownRenderer::with_symbols(Arc::new(SymbolIndex::new())).render_meta(&file)?;
+
+        assert!(md.contains("| ~~[`id`](#Song.id)~~ | `integer` |"), "{}", md);
+        assert!(md.contains("##### id <a id=\"Song.id\"></a>\n> **Deprecated**\n\n<kbd>private</kbd> `integer`<br />"), "{}", md);
+        assert!(md.contains("> **Deprecated**: Use `Song:open` instead.\n\n<kbd>async</kbd> <kbd>nodiscard</kbd> \n"), "{}", md);
+
+        Ok(())
+    }
+}
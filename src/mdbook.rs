@@ -1,47 +1,128 @@
-use anyhow::anyhow;
 use mdbook::{book::{Book, Chapter, SectionNumber}, preprocess::{Preprocessor, PreprocessorContext}, BookItem};
 use mdbook::errors::Error as MdBookError;
-use tempdir::TempDir;
-use std::{env, fs::{self}, path::PathBuf, process::Command};
+use std::{env, path::{Path, PathBuf}, sync::Arc};
+use pulldown_cmark::{Event, Options, Parser, Tag};
 use toml::value::Table;
 use log::*;
 
-use crate::{doctree::{build_docs, MetaFile}, errors::*, json::Definition, location::FileUri, markdown::{self, MarkdownRenderer}, workspace::Workspace};
+use crate::{doctree::{DocTree, ItemRef, MetaFile, PageGranularity}, errors::*, generate::{load_docs, renderer, DocOptions}, index::render_index_pages, links::rewrite_links, location::PathRemap, markdown::MarkdownRenderer, symbols::SymbolIndex};
 
 /// Configuration for the preprocessor.
 #[derive(Debug, Default)]
 pub struct Config {
     definitions_path: Option<PathBuf>,
+    docs: DocOptions,
     part_title: Option<String>,
     nav_depth: Option<u8>,
 }
 
-impl<'a> From<Option<&'a Table>> for Config {
-    fn from(table: Option<&'a Table>) -> Config {
+impl<'a> TryFrom<Option<&'a Table>> for Config {
+    type Error = Error;
+
+    fn try_from(table: Option<&'a Table>) -> Result<Config> {
         let mut config = Config::default();
 
         if let Some(table) = table {
             config.definitions_path = table
                 .get("definitions-path")
                 .and_then(|v| v.as_str())
-                .and_then(|v| Some(v.to_owned().into()));
+                .map(|v| v.to_owned().into());
+
+            config.docs.doc_json_path = table
+                .get("doc-json-path")
+                .and_then(|v| v.as_str())
+                .map(|v| v.to_owned().into());
+
+            config.docs.path_remaps = table
+                .get("path-remap")
+                .and_then(|v| v.as_array())
+                .map(|remaps| {
+                    remaps
+                        .iter()
+                        .filter_map(|remap| {
+                            let from = remap.get("from").and_then(|v| v.as_str())?;
+                            let to = remap.get("to").and_then(|v| v.as_str())?;
+
+                            Some(PathRemap::new(from, to))
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
 
             config.part_title = table
                 .get("part-title")
                 .and_then(|v| v.as_str())
-                .and_then(|v| Some(v.to_owned()));
+                .map(|v| v.to_owned());
+
+            config.docs.inherited_members = table
+                .get("inherited-members")
+                .and_then(|v| v.as_bool())
+                .unwrap_or_default();
+
+            config.docs.item_order = table
+                .get("item-order")
+                .and_then(|v| v.as_str())
+                .and_then(|v| match v.parse() {
+                    Ok(order) => Some(order),
+                    Err(e) => {
+                        warn!("{}", e);
+                        None
+                    },
+                })
+                .unwrap_or_default();
+
+            config.docs.template_dir = table
+                .get("template-dir")
+                .and_then(|v| v.as_str())
+                .map(|v| v.to_owned().into());
+
+            config.docs.page_granularity = table
+                .get("page-granularity")
+                .and_then(|v| v.as_str())
+                .and_then(|v| match v.parse() {
+                    Ok(granularity) => Some(granularity),
+                    Err(e) => {
+                        warn!("{}", e);
+                        None
+                    },
+                })
+                .unwrap_or_default();
+
+            config.docs.index_chapters = table
+                .get("index-chapters")
+                .and_then(|v| v.as_bool())
+                .unwrap_or_default();
+
+            config.docs.strict = table
+                .get("strict")
+                .and_then(|v| v.as_bool())
+                .unwrap_or_default();
+
+            // Falling back to a default could publish hidden members
+            if let Some(visibility) = table.get("visibility") {
+                config.docs.visibility = visibility
+                    .as_str()
+                    .ok_or_else(|| anyhow!("Invalid visibility {}, expected a string", visibility))?
+                    .parse()?;
+            }
+
+            config.docs.hide_underscored = table
+                .get("hide-underscored")
+                .and_then(|v| v.as_bool())
+                .unwrap_or_default();
 
             config.nav_depth = table
                 .get("nav-depth")
                 .and_then(|v| v.as_integer())
-                .and_then(|v| Some(v.try_into().expect("nav-depth overflow")));
+                .map(|v| v.try_into().expect("nav-depth overflow"));
         }
 
-        config
+        Ok(config)
     }    
 }
 
 /// A mdbook preprocessor that generates LuaCATS API docs.
+#[derive(Default)]
 pub struct MoonCats;
 
 impl MoonCats {
@@ -50,19 +131,13 @@ impl MoonCats {
     }
 }
 
-impl Default for MoonCats {
-    fn default() -> Self {
-        Self {}
-    }
-}
-
 impl Preprocessor for MoonCats {
     fn name(&self) -> &str {
         "mooncats-preprocessor"
     }
 
     fn run(&self, ctx: &PreprocessorContext, mut book: Book) -> Result<Book, MdBookError> {
-        let config: Config = ctx.config.get_preprocessor(self.name()).into();
+        let config = Config::try_from(ctx.config.get_preprocessor(self.name()))?;
 
         debug!("Using mdbook root: {:?}", ctx.root);
         debug!("Using definitions path: {:?}", config.definitions_path);
@@ -78,27 +153,93 @@ impl Preprocessor for MoonCats {
         }
         debug!("Using root path: {:?}", root_path);
 
-        let docs = generate_json_docs(&root_path)?;
-        debug!("Generated {} definitions", docs.len());
+        let mut options = config.docs;
+        for remap in options.path_remaps.iter_mut() {
+            if remap.to.is_relative() {
+                remap.to = root.join(&remap.to);
+            }
+        }
+        if let Some(doc_json_path) = options.doc_json_path.as_mut() {
+            if doc_json_path.is_relative() {
+                *doc_json_path = root.join(&doc_json_path);
+            }
+        }
+        if let Some(template_dir) = options.template_dir.as_mut() {
+            if template_dir.is_relative() {
+                *template_dir = root.join(&template_dir);
+            }
+        }
+
+        let doc_tree = load_docs(&root_path, &options)?;
+
+        let symbols = SymbolIndex::build_with_pages(&doc_tree, &root_path, options.page_granularity)?;
+        debug!("Indexed {} symbols", symbols.len());
 
-        let root_uri: FileUri = root_path.clone().try_into()?;
-        
-        let mut workspace = Workspace::new(root_uri);
-        workspace.load(docs)?;
-        debug!("Loaded {} root files", workspace.files.len());
+        let symbols = Arc::new(symbols);
+        let md = renderer(symbols.clone(), &options)?;
 
-        let doc_tree = build_docs(workspace)?;
+        let mut result = Ok(());
+        book.for_each_mut(|item| {
+            let BookItem::Chapter(chapter) = item else {
+                return
+            };
+            let Some(path) = &chapter.path else {
+                return
+            };
 
-        let md = MarkdownRenderer::new();
+            if result.is_ok() {
+                match expand_embeds(&chapter.content, &doc_tree, &md, path) {
+                    Ok(content) => chapter.content = content,
+                    Err(e) => result = Err(e),
+                }
+            }
+        });
+        result?;
+
+        let mut chapters = doc_tree
+            .files()
+            .iter()
+            .enumerate()
+            .map(|(index, file)| {
+                let chapter = build_chapter(&md, &root_path, file, options.page_granularity, index, None)?;
+                Ok(BookItem::Chapter(chapter))
+            })
+            .collect::<Result<Vec<BookItem>>>()?;
+
+        if options.index_chapters {
+            for page in render_index_pages(&doc_tree, &root_path, &md)? {
+                let chapter = new_chapter(page.title.to_string(), page.content, page.path, chapters.len(), None);
+                chapters.push(BookItem::Chapter(chapter));
+            }
+        }
 
-        let part_title = config.part_title.unwrap_or("API Reference".into());
-        book.push_item(BookItem::PartTitle(part_title));
+        if splice_placeholder(&mut book.sections, &mut chapters) {
+            debug!("Inserted chapters at placeholder");
+            renumber_chapters(&mut book.sections, &SectionNumber::default(), &mut 0);
+        } else {
+            let part_title = config.part_title.unwrap_or("API Reference".into());
+            book.push_item(BookItem::PartTitle(part_title));
 
-        for (index, file) in doc_tree.into_iter().enumerate() {
-            let chapter = build_chapter(&md, &root_path, &file, index, None)?;
-            book.push_item(BookItem::Chapter(chapter));
+            for chapter in chapters {
+                book.push_item(chapter);
+            }
         }
 
+        book.for_each_mut(|item| {
+            let BookItem::Chapter(chapter) = item else {
+                return
+            };
+            let Some(path) = &chapter.path else {
+                return
+            };
+
+            let (content, missing) = rewrite_links(&chapter.content, path, &symbols);
+            for name in missing {
+                warn!("{}: link to missing item {:?}", chapter.name, name);
+            }
+            chapter.content = content;
+        });
+
         Ok(book)
     }
 
@@ -107,12 +248,31 @@ impl Preprocessor for MoonCats {
     }
 }
 
-fn build_chapter(md: &MarkdownRenderer, base: &PathBuf, file: &MetaFile, index: usize, parent: Option<&Chapter>) -> anyhow::Result<Chapter> {
+fn build_chapter(md: &MarkdownRenderer, base: &PathBuf, file: &MetaFile, granularity: PageGranularity, index: usize, parent: Option<&Chapter>) -> anyhow::Result<Chapter> {
     let name = file.uri.file_stem(); 
     let content = md.render_meta(file)?;
-    let md_path = file.uri.to_file_path()?
-        .strip_prefix(base)?
-        .with_extension("md");
+    let md_path = file.page_path(base)?;
+    let mut chapter = new_chapter(name, content, md_path, index, parent);
+
+    let mut sub_items = Vec::new();
+    for item in file.page_items(granularity) {
+        let path = file.item_page_path(base, item)?;
+        let content = md.render_item(ItemRef::Item(item), &path)?;
+        let item_chapter = new_chapter(item.name.clone(), content, path, sub_items.len(), Some(&chapter));
+        sub_items.push(BookItem::Chapter(item_chapter));
+    }
+    for sub_file in file.children.iter() {
+        let sub_chapter = build_chapter(md, base, sub_file, granularity, sub_items.len(), Some(&chapter))?;
+        sub_items.push(BookItem::Chapter(sub_chapter));
+    }
+    chapter.sub_items = sub_items;
+
+    Ok(chapter)
+}
+
+/// A chapter numbered as the top level chapter at the index, or the
+/// sub-chapter of the parent at the index.
+fn new_chapter(name: String, content: String, md_path: PathBuf, index: usize, parent: Option<&Chapter>) -> Chapter {
     let number = match parent {
         Some(parent) => {
             let mut number = parent.number.clone().unwrap_or_else(|| SectionNumber(Vec::new()));
@@ -130,7 +290,7 @@ fn build_chapter(md: &MarkdownRenderer, base: &PathBuf, file: &MetaFile, index:
         None => Vec::new(),
     };
 
-    let mut chapter = Chapter {
+    Chapter {
         name,
         content,
         number: Some(number),
@@ -138,59 +298,184 @@ fn build_chapter(md: &MarkdownRenderer, base: &PathBuf, file: &MetaFile, index:
         path: Some(md_path),
         source_path: None,
         parent_names,
-    };
+    }
+}
 
-    chapter.sub_items = file.children
-        .iter()
-        .enumerate()
-        .map(|(sub_index, sub_file)| -> anyhow::Result<BookItem> {
-            let chapter = build_chapter(md, base, sub_file, sub_index, Some(&chapter))?;
-            Ok(BookItem::Chapter(chapter))
-        })
-        .collect::<anyhow::Result<Vec<BookItem>>>()?;
+/// Marks the chapter in SUMMARY.md to replace with the generated chapters,
+/// either as the chapter content or as the name of a draft chapter.
+const PLACEHOLDER: &str = "{{#mooncats}}";
 
-    Ok(chapter)
+fn is_placeholder(chapter: &Chapter) -> bool {
+    chapter.content.trim() == PLACEHOLDER
+        || (chapter.is_draft_chapter() && chapter.name.trim() == PLACEHOLDER)
 }
 
-/// Spawn the lua-language-server to generate docs.
-fn generate_json_docs(definitions_path: &PathBuf) -> Result<Vec<Definition>> { 
-    let tmp_dir = TempDir::new("luals-docs")?;
-    let tmp_path = tmp_dir.path();
-
-    let output = Command::new("lua-language-server")
-        .arg("--doc")
-        .arg(definitions_path)
-        .arg("--doc_out_path")
-        .arg(tmp_path)
-        .arg("--logpath")
-        .arg(tmp_path)
-        .output()?;
-
-    if !output.status.success() {
-        let err = match output.status.code() {
-            Some(code) => anyhow!("LuaLS process exited with status code {}", code),
-            None => anyhow!("LuaLS process terminated by signal"),
+/// Replace the first placeholder chapter with the given chapters, followed by
+/// the chapters nested under the placeholder. Returns false if there's no
+/// placeholder.
+fn splice_placeholder(items: &mut Vec<BookItem>, chapters: &mut Vec<BookItem>) -> bool {
+    for index in 0..items.len() {
+        let BookItem::Chapter(chapter) = &mut items[index] else {
+            continue
         };
-        return Err(err)
+
+        if !is_placeholder(chapter) {
+            if splice_placeholder(&mut chapter.sub_items, chapters) {
+                return true
+            }
+            continue
+        }
+
+        let numbered = chapter.number.is_some();
+        let parent_names = chapter.parent_names.clone();
+        let mut sub_items = std::mem::take(&mut chapter.sub_items);
+        for item in sub_items.iter_mut() {
+            if let BookItem::Chapter(chapter) = item {
+                unnest_chapter(chapter, parent_names.len());
+            }
+        }
+        let mut chapters = std::mem::take(chapters);
+        for item in chapters.iter_mut() {
+            if let BookItem::Chapter(chapter) = item {
+                nest_chapter(chapter, &parent_names, numbered);
+            }
+        }
+
+        items.splice(index..=index, chapters.into_iter().chain(sub_items));
+        return true
     }
 
-    let json_doc_path = tmp_dir.path().join("doc.json");
+    false
+}
+
+/// Move a chapter out of its parent at the given depth.
+fn unnest_chapter(chapter: &mut Chapter, depth: usize) {
+    if depth < chapter.parent_names.len() {
+        chapter.parent_names.remove(depth);
+    }
 
-    let json_doc = fs::read_to_string(json_doc_path)?;
+    for item in chapter.sub_items.iter_mut() {
+        if let BookItem::Chapter(sub_chapter) = item {
+            unnest_chapter(sub_chapter, depth);
+        }
+    }
+}
 
-    let definitions: Vec<Definition> = serde_json::from_str(&json_doc)?;
+/// Move a generated chapter under the given parents.
+fn nest_chapter(chapter: &mut Chapter, parent_names: &[String], numbered: bool) {
+    chapter.parent_names.splice(0..0, parent_names.iter().cloned());
+    if !numbered {
+        chapter.number = None;
+    }
 
-    Ok(definitions)
+    for item in chapter.sub_items.iter_mut() {
+        if let BookItem::Chapter(sub_chapter) = item {
+            nest_chapter(sub_chapter, parent_names, numbered);
+        }
+    }
+}
+
+/// Number chapters in order, like mdbook does when loading SUMMARY.md.
+/// Top level numbering continues across parts.
+fn renumber_chapters(items: &mut [BookItem], parent: &SectionNumber, count: &mut u32) {
+    for item in items.iter_mut() {
+        let BookItem::Chapter(chapter) = item else {
+            continue
+        };
+
+        if chapter.number.is_some() {
+            *count += 1;
+            let mut number = parent.clone();
+            number.0.push(*count);
+            chapter.number = Some(number);
+        }
+
+        let number = chapter.number.clone().unwrap_or_default();
+        renumber_chapters(&mut chapter.sub_items, &number, &mut 0);
+    }
+}
+
+/// Starts a `{{#luacats name}}` expression, which embeds the docs for an
+/// item or member in a chapter.
+const EMBED_START: &str = "{{#luacats ";
+
+/// Replace each embed expression in the content with the rendered item.
+/// Expressions naming a missing item are left as they are, as are those in
+/// code and those escaped as `\{{#luacats name}}`, which lose the backslash.
+fn expand_embeds(content: &str, tree: &DocTree, md: &MarkdownRenderer, page: &Path) -> Result<String> {
+    let code = code_ranges(content);
+    let mut expanded = String::with_capacity(content.len());
+    let mut offset = 0;
+
+    while let Some(found) = content[offset..].find(EMBED_START) {
+        let start = offset + found;
+        let Some(len) = content[start..].find("}}") else {
+            break
+        };
+        let end = start + len + 2;
+        let expression = &content[start..end];
+        let name = expression[EMBED_START.len()..len].trim();
+
+        if code.iter().any(|range| range.contains(&start)) {
+            expanded.push_str(&content[offset..end]);
+        } else if content[..start].ends_with('\\') {
+            expanded.push_str(&content[offset..start - 1]);
+            expanded.push_str(expression);
+        } else {
+            expanded.push_str(&content[offset..start]);
+            match tree.find(name) {
+                Some(item) => expanded.push_str(md.render_item(item, page)?.trim()),
+                None => {
+                    warn!("{}: no documented item named {:?}", page.display(), name);
+                    expanded.push_str(expression);
+                },
+            }
+        }
+
+        offset = end;
+    }
+
+    expanded.push_str(&content[offset..]);
+
+    Ok(expanded)
+}
+
+/// The source ranges of the code spans and code blocks in the content.
+fn code_ranges(content: &str) -> Vec<std::ops::Range<usize>> {
+    Parser::new_ext(content, Options::all())
+        .into_offset_iter()
+        .filter_map(|(event, span)| match event {
+            Event::Code(_) | Event::Start(Tag::CodeBlock(_)) => Some(span),
+            _ => None,
+        })
+        .collect()
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::doctree::VisibilityFilter;
 
     fn init() {
         let _ = env_logger::builder().is_test(true).try_init();
     }
 
+    #[test]
+    fn parse_visibility_config() -> Result<()> {
+        let parse = |toml: &str| -> Result<VisibilityFilter> {
+            let table: Table = toml::from_str(toml)?;
+            Ok(Config::try_from(Some(&table))?.docs.visibility)
+        };
+
+        assert_eq!(Config::try_from(None)?.docs.visibility, VisibilityFilter::Public);
+        assert_eq!(parse("")?, VisibilityFilter::Public);
+        assert_eq!(parse("visibility = \"protected\"")?, VisibilityFilter::Protected);
+        assert!(parse("visibility = \"publc\"").unwrap_err().to_string().contains("unknown visibility \"publc\""));
+        assert!(parse("visibility = true").is_err());
+
+        Ok(())
+    }
+
     #[test]
     fn preprocessor_run() {
         init();
@@ -243,4 +528,180 @@ mod test {
         // TODO: better asserts
         assert_eq!(actual_book.sections.len(), 2); // Chapter 1, Chapter "hello"
     }
+
+    /// Run the preprocessor over the test data with the given book sections.
+    fn run_with_doc_json(sections: &str) -> Book {
+        init();
+
+        let root = env!("CARGO_MANIFEST_DIR");
+        let input_json = format!(r##"[
+            {{
+                "root": "{root}",
+                "config": {{
+                    "book": {{
+                        "authors": ["AUTHOR"],
+                        "language": "en",
+                        "multilingual": false,
+                        "src": "src",
+                        "title": "TITLE"
+                    }},
+                    "preprocessor": {{
+                        "mooncats-preprocessor": {{
+                            "definitions-path": "testdata",
+                            "doc-json-path": "testdata/doc.json",
+                            "path-remap": [
+                                {{ "from": "/Users/matt/Code/mooncats", "to": "." }}
+                            ]
+                        }}
+                    }}
+                }},
+                "renderer": "html",
+                "mdbook_version": "0.4.21"
+            }},
+            {{
+                "sections": {sections},
+                "__non_exhaustive": null
+            }}
+        ]"##);
+
+        let (ctx, book) = mdbook::preprocess::CmdPreprocessor::parse_input(input_json.as_bytes()).unwrap();
+        let result = MoonCats::new().run(&ctx, book);
+        assert!(result.is_ok(), "preprocessor failed: {:#?}", result.err());
+
+        result.unwrap()
+    }
+
+    #[test]
+    fn preprocessor_run_with_doc_json() {
+        let actual_book = run_with_doc_json("[]");
+
+        assert_eq!(actual_book.sections.len(), 2); // Part title, Chapter "test"
+        match &actual_book.sections[1] {
+            BookItem::Chapter(chapter) => {
+                assert_eq!(chapter.name, "test");
+                assert!(chapter.content.contains("AClass"));
+            },
+            item => panic!("expected chapter, got {:?}", item),
+        }
+    }
+
+    fn chapter(name: &str, number: &[u32], content: &str, sub_items: Vec<BookItem>) -> BookItem {
+        let mut chapter = Chapter::new(name, content.to_string(), format!("{}.md", name), Vec::new());
+        chapter.number = (!number.is_empty()).then(|| SectionNumber(number.to_vec()));
+        chapter.sub_items = sub_items;
+        BookItem::Chapter(chapter)
+    }
+
+    #[test]
+    fn preprocessor_run_with_placeholder() {
+        let sections = serde_json::to_string(&vec![
+            chapter("intro", &[1], "# Intro", Vec::new()),
+            chapter("api", &[2], "{{#mooncats}}\n", Vec::new()),
+            chapter("appendix", &[3], "# Appendix", Vec::new()),
+        ]).unwrap();
+
+        let actual_book = run_with_doc_json(&sections);
+
+        let chapters: Vec<(&str, Option<&SectionNumber>)> = actual_book
+            .iter()
+            .filter_map(|item| match item {
+                BookItem::Chapter(chapter) => Some((chapter.name.as_str(), chapter.number.as_ref())),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(actual_book.sections.len(), 3);
+        assert_eq!(chapters, vec![
+            ("intro", Some(&SectionNumber(vec![1]))),
+            ("test", Some(&SectionNumber(vec![2]))),
+            ("appendix", Some(&SectionNumber(vec![3]))),
+        ]);
+    }
+
+    #[test]
+    fn splice_nested_placeholder() {
+        let mut changelog = chapter("changelog", &[1, 2, 1], "", Vec::new());
+        if let BookItem::Chapter(changelog) = &mut changelog {
+            changelog.parent_names = vec!["guide".to_string(), "api".to_string()];
+        }
+        let mut api = chapter("api", &[1, 2], " {{#mooncats}} ", vec![changelog]);
+        if let BookItem::Chapter(api) = &mut api {
+            api.parent_names = vec!["guide".to_string()];
+        }
+        let mut items = vec![
+            BookItem::PartTitle("Guide".to_string()),
+            chapter("guide", &[1], "", vec![
+                chapter("setup", &[1, 1], "", Vec::new()),
+                api,
+                chapter("faq", &[1, 3], "", Vec::new()),
+            ]),
+            BookItem::PartTitle("Appendix".to_string()),
+            chapter("glossary", &[2], "", Vec::new()),
+        ];
+        let mut generated = vec![
+            chapter("renoise", &[1], "", vec![chapter("song", &[1, 1], "", Vec::new())]),
+            chapter("bit", &[2], "", Vec::new()),
+        ];
+
+        assert!(splice_placeholder(&mut items, &mut generated));
+        renumber_chapters(&mut items, &SectionNumber::default(), &mut 0);
+
+        let mut book = Book::new();
+        book.sections = items;
+        let chapters: Vec<(&str, String, &[String])> = book
+            .iter()
+            .filter_map(|item| match item {
+                BookItem::Chapter(chapter) => Some((
+                    chapter.name.as_str(),
+                    chapter.number.as_ref().map(|n| n.to_string()).unwrap_or_default(),
+                    chapter.parent_names.as_slice(),
+                )),
+                _ => None,
+            })
+            .collect();
+        let guide = ["guide".to_string()];
+        assert_eq!(chapters, vec![
+            ("guide", "1.".to_string(), &[][..]),
+            ("setup", "1.1.".to_string(), &[][..]),
+            ("renoise", "1.2.".to_string(), &guide[..]),
+            ("song", "1.2.1.".to_string(), &guide[..]),
+            ("bit", "1.3.".to_string(), &guide[..]),
+            ("changelog", "1.4.".to_string(), &guide[..]),
+            ("faq", "1.5.".to_string(), &[][..]),
+            ("glossary", "2.".to_string(), &[][..]),
+        ]);
+
+        assert!(!splice_placeholder(&mut Vec::new(), &mut Vec::new()));
+    }
+
+    #[test]
+    fn preprocessor_run_with_embeds() {
+        let content = "# Guide\n\n{{#luacats AClass}}\n\n{{#luacats AClass:do_stuff}}\n\n{{#luacats missing}}\n";
+        let sections = serde_json::to_string(&vec![chapter("guide", &[1], content, Vec::new())]).unwrap();
+
+        let actual_book = run_with_doc_json(&sections);
+
+        let BookItem::Chapter(guide) = &actual_book.sections[0] else {
+            panic!("expected chapter, got {:?}", actual_book.sections[0])
+        };
+        assert!(guide.content.starts_with("# Guide\n\n### AClass"), "{}", guide.content);
+        assert!(guide.content.contains("##### foo <a id=\"AClass.foo\"></a>"), "{}", guide.content);
+        assert!(guide.content.contains("##### do_stuff <a id=\"AClass.do_stuff\"></a>\n\n```lua\nAClass:do_stuff()"), "{}", guide.content);
+        assert!(guide.content.ends_with("\n\n{{#luacats missing}}\n"), "{}", guide.content);
+    }
+
+    #[test]
+    fn keep_embeds_in_code() {
+        let content = "Use `{{#luacats AClass}}` or\n\n```md\n{{#luacats AClass}}\n```\n\n\\{{#luacats AClass}} and `\\{{#luacats AClass}}`\n";
+        let sections = serde_json::to_string(&vec![chapter("guide", &[1], content, Vec::new())]).unwrap();
+
+        let actual_book = run_with_doc_json(&sections);
+
+        let BookItem::Chapter(guide) = &actual_book.sections[0] else {
+            panic!("expected chapter, got {:?}", actual_book.sections[0])
+        };
+        assert_eq!(
+            guide.content,
+            "Use `{{#luacats AClass}}` or\n\n```md\n{{#luacats AClass}}\n```\n\n{{#luacats AClass}} and `\\{{#luacats AClass}}`\n"
+        );
+    }
 }
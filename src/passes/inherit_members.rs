@@ -0,0 +1,124 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use log::debug;
+
+use crate::{doctree::{Class, DocItemEnum, DocTree, InheritedMembers}, lua_type::LuaType};
+
+pub fn inherit_members(tree: &mut DocTree) {
+    let mut classes: HashMap<String, Class> = HashMap::new();
+
+    tree.for_each(|file| {
+        for item in file.items.values() {
+            if let DocItemEnum::Class(class) = &item.inner {
+                classes.insert(item.name.clone(), class.clone());
+            }
+        }
+    });
+
+    tree.for_each_mut(|file| {
+        for item in file.items.values_mut() {
+            if let DocItemEnum::Class(ref mut class) = item.inner {
+                class.inherited = inherited_members(&item.name, class, &classes);
+            }
+        }
+    });
+}
+
+/// Walk the ancestors breadth first, so members from the nearest ancestor
+/// shadow members of the same name further up the hierarchy.
+fn inherited_members(name: &str, class: &Class, classes: &HashMap<String, Class>) -> Vec<InheritedMembers> {
+    let mut seen_classes: HashSet<&str> = HashSet::from([name]);
+    let mut seen_members: HashSet<&str> = class.fields
+        .iter()
+        .map(|f| f.name.as_str())
+        .chain(class.methods.iter().map(|m| m.name.as_str()))
+        .collect();
+    let mut queue: VecDeque<&str> = class.parents.iter().filter_map(parent_name).collect();
+    let mut inherited = Vec::new();
+
+    while let Some(parent) = queue.pop_front() {
+        if !seen_classes.insert(parent) {
+            continue
+        }
+
+        let Some(parent_class) = classes.get(parent) else {
+            debug!("Skipping unknown parent class {} of {}", parent, name);
+            continue
+        };
+
+        let fields: Vec<_> = parent_class.fields
+            .iter()
+            .filter(|f| !seen_members.contains(f.name.as_str()))
+            .cloned()
+            .collect();
+        let methods: Vec<_> = parent_class.methods
+            .iter()
+            .filter(|m| !seen_members.contains(m.name.as_str()))
+            .cloned()
+            .collect();
+
+        seen_members.extend(parent_class.fields.iter().map(|f| f.name.as_str()));
+        seen_members.extend(parent_class.methods.iter().map(|m| m.name.as_str()));
+        queue.extend(parent_class.parents.iter().filter_map(parent_name));
+
+        if !fields.is_empty() || !methods.is_empty() {
+            inherited.push(InheritedMembers {
+                class: parent.to_string(),
+                fields,
+                methods,
+            });
+        }
+    }
+
+    inherited
+}
+
+fn parent_name(parent: &LuaType) -> Option<&str> {
+    match parent {
+        LuaType::Name { name } | LuaType::Generic { name, .. } => Some(name),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        doctree::{DocItem, Field, MetaFile},
+        location::FileUri,
+        testing::{item, range},
+    };
+
+    fn class_item(name: &str, parents: &[&str], fields: &[&str]) -> DocItem {
+        item(name, DocItemEnum::Class(Class {
+            parents: parents.iter().map(|p| LuaType::name(*p)).collect(),
+            fields: fields
+                .iter()
+                .map(|f| Field::new(f.to_string(), None, LuaType::name("string"), range(0)))
+                .collect(),
+            ..Class::default()
+        }))
+    }
+
+    #[test]
+    fn inherit_from_ancestors() {
+        let mut file = MetaFile::new(FileUri::parse("file:///library/shapes.lua").unwrap());
+        file.add_item(class_item("Shape", &[], &["name", "id"]));
+        file.add_item(class_item("Rect", &["Shape"], &["width", "name"]));
+        file.add_item(class_item("Square", &["Rect"], &["width"]));
+        let mut tree = DocTree::new();
+        tree.add_item(file);
+
+        tree.add_inherited_members();
+
+        let mut square = None;
+        tree.for_each(|file| square = file.items.get("Square").cloned());
+        let DocItemEnum::Class(square) = square.unwrap().inner else { panic!("expected class") };
+        let inherited: Vec<(&str, Vec<&str>)> = square.inherited
+            .iter()
+            .map(|i| (i.class.as_str(), i.fields.iter().map(|f| f.name.as_str()).collect()))
+            .collect();
+
+        assert_eq!(inherited, vec![("Rect", vec!["name"]), ("Shape", vec!["id"])]);
+    }
+}
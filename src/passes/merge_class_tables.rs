@@ -1,38 +1,122 @@
 use log::debug;
 
-use crate::{doctree::{DocItem, DocItemEnum, MetaFile}, errors::*, workspace::SourceFile};
+use crate::{diagnostics::Diagnostics, doctree::{DocItemEnum, MetaFile}, workspace::SourceFile};
 
-pub fn merge_class_tables(meta_file: &mut MetaFile, _source_file: &SourceFile) -> Result<()> {
-    let tables: Vec<&DocItem> = meta_file.items
+/// Merge each table whose LuaLS view is a class in this file into the class.
+/// The table's description, fields and functions are added to the class,
+/// after its own members, unless the class already has them.
+pub fn merge_class_tables(meta_file: &mut MetaFile, _source_file: &SourceFile, _diagnostics: &mut Diagnostics) {
+    let merges: Vec<(String, String)> = meta_file.items
         .values()
-        .filter(|item| matches!(item.inner, DocItemEnum::Table(_)))
+        .filter_map(|item| match &item.inner {
+            DocItemEnum::Table(table) => Some((item.name.clone(), table.view.clone())),
+            _ => None,
+        })
+        .filter(|(_, view)| {
+            meta_file.items
+                .get(view)
+                .is_some_and(|item| matches!(item.inner, DocItemEnum::Class(_)))
+        })
         .collect();
 
-    let classes: Vec<&DocItem> = meta_file.items
-        .values()
-        .filter(|item| matches!(item.inner, DocItemEnum::Class(_)))
-        .collect();
+    for (table_name, class_name) in merges {
+        let Some(table_item) = meta_file.items.shift_remove(&table_name) else {
+            continue
+        };
+        let DocItemEnum::Table(table) = table_item.inner else {
+            continue
+        };
+        let Some(class_item) = meta_file.items.get_mut(&class_name) else {
+            continue
+        };
+        let DocItemEnum::Class(ref mut class) = class_item.inner else {
+            continue
+        };
+
+        if class_item.description.is_none() {
+            class_item.description = table_item.description;
+        }
+        if class_item.deprecated.is_none() {
+            class_item.deprecated = table_item.deprecated;
+        }
+
+        let mut merged = Vec::new();
+        let mut skipped = Vec::new();
+
+        for field in table.fields.into_values() {
+            if class.fields.iter().any(|existing| existing.name == field.name) {
+                skipped.push(field.name);
+            } else {
+                merged.push(field.name.clone());
+                class.fields.push(field);
+            }
+        }
 
-    let mut removals = Vec::new();
-
-    // TODO: cleaner way to do this match?
-    for table_item in tables.iter() {
-        for class_item in classes.iter() {
-            match &table_item.inner {
-                DocItemEnum::Table(table) => {
-                    if table.view == class_item.name {
-                        debug!("Merging table {} with class {}", table_item.name, class_item.name);
-                        removals.push(table_item.name.clone());
-                    }
-                },
-                _ => bail!("expected table"),
+        for function in table.functions.into_values() {
+            if class.methods.iter().any(|existing| existing.name == function.name) {
+                skipped.push(function.name);
+            } else {
+                merged.push(function.name.clone());
+                class.methods.push(function);
             }
         }
+
+        debug!(
+            "Merged table {} into class {}, adding {:?} and skipping {:?}",
+            table_name, class_name, merged, skipped
+        );
     }
+}
 
-    for key in removals.iter() {
-        meta_file.items.remove(key);
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        doctree::{Class, DocItem, Field, Function, NamedFunction, Table},
+        location::FileUri,
+        lua_type::LuaType,
+        testing::{function, item, range},
+    };
+
+    fn bit_function(name: &str, line: u64) -> NamedFunction {
+        NamedFunction::new(name.to_string(), Function {
+            range: range(line),
+            ..function(&format!("function bit.{}(x: integer)", name))
+        })
     }
 
-    Ok(())
-}
\ No newline at end of file
+    #[test]
+    fn merge_table_members() {
+        let uri = FileUri::parse("file:///library/bit.lua").unwrap();
+        let source_file = SourceFile::new(uri.clone(), String::new());
+
+        let mut table = Table {
+            view: "bitlib".to_string(),
+            ..Table::default()
+        };
+        table.add_function(bit_function("band", 3));
+        table.add_function(bit_function("tobit", 4));
+        table.add_field(Field::new("bits".to_string(), None, LuaType::name("integer"), range(5)));
+        let class = Class {
+            methods: vec![bit_function("bor", 1), bit_function("band", 2)],
+            ..Class::default()
+        };
+
+        let mut meta_file = MetaFile::new(uri);
+        meta_file.add_item(item("bitlib", DocItemEnum::Class(class)));
+        meta_file.add_item(DocItem {
+            description: Some("Bitwise operations.".to_string()),
+            ..item("bit", DocItemEnum::Table(table))
+        });
+
+        merge_class_tables(&mut meta_file, &source_file, &mut Diagnostics::new());
+
+        assert_eq!(meta_file.items.keys().collect::<Vec<_>>(), ["bitlib"]);
+        let class_item = &meta_file.items["bitlib"];
+        let class = class_item.as_class().unwrap();
+        assert_eq!(class_item.description(), Some("Bitwise operations."));
+        assert_eq!(class.methods.iter().map(|method| method.name()).collect::<Vec<_>>(), ["bor", "band", "tobit"]);
+        assert_eq!(class.methods[1].function.range, range(2));
+        assert_eq!(class.fields.iter().map(|field| field.name()).collect::<Vec<_>>(), ["bits"]);
+    }
+}
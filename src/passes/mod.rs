@@ -1,8 +1,16 @@
+mod inherit_members;
 mod merge_class_tables;
+mod parse_annotations;
 mod parse_items;
+mod parse_overloads;
 mod parse_set_fields;
 mod parse_table_fields;
+mod report_unknown_types;
+pub(crate) use inherit_members::inherit_members;
 pub(crate) use merge_class_tables::merge_class_tables;
+pub(crate) use parse_annotations::parse_annotations;
 pub(crate) use parse_items::parse_items;
+pub(crate) use parse_overloads::parse_overloads;
 pub(crate) use parse_set_fields::parse_set_fields;
-pub(crate) use parse_table_fields::parse_table_fields;
\ No newline at end of file
+pub(crate) use parse_table_fields::parse_table_fields;
+pub(crate) use report_unknown_types::report_unknown_types;
\ No newline at end of file
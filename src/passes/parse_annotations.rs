@@ -0,0 +1,204 @@
+use crate::{doctree::{Deprecation, DocItemEnum, Field, Function, Global, MetaFile, NamedFunction, Visibility}, location::Range, workspace::{tag_values, SourceFile}};
+
+/// Add the `---@deprecated` messages, `---@nodiscard`, and `---@async` and
+/// the visibility of table members. LuaLS doesn't export these, so they are
+/// read from the source.
+pub fn parse_annotations(meta_file: &mut MetaFile, source_file: &SourceFile) {
+    for item in meta_file.items.values_mut() {
+        let annotations = Annotations::read(source_file, item.range.start().line);
+        annotations.deprecate(&mut item.deprecated);
+
+        match item.inner {
+            DocItemEnum::Class(ref mut class) => {
+                class.fields.iter_mut().for_each(|field| annotate_field(field, source_file));
+                class.methods.iter_mut().for_each(|method| annotate_function(method, source_file));
+            },
+            DocItemEnum::Table(ref mut table) => {
+                table.fields.values_mut().for_each(|field| annotate_field(field, source_file));
+                table.functions.values_mut().for_each(|function| annotate_function(function, source_file));
+            },
+            DocItemEnum::Global(Global::Function(ref mut function)) => annotations.annotate(function),
+            _ => {},
+        }
+    }
+}
+
+fn annotate_field(field: &mut Field, source_file: &SourceFile) {
+    if let Some(annotations) = Annotations::read_member(source_file, field.range, &field.name) {
+        annotations.deprecate(&mut field.deprecated);
+        annotations.restrict(&mut field.visibility);
+    }
+}
+
+fn annotate_function(function: &mut NamedFunction, source_file: &SourceFile) {
+    if let Some(annotations) = Annotations::read_member(source_file, function.function.range, &function.name) {
+        annotations.deprecate(&mut function.deprecated);
+        annotations.restrict(&mut function.visibility);
+        annotations.annotate(&mut function.function);
+    }
+}
+
+/// The annotations in the doc comment of a definition.
+struct Annotations<'a> {
+    deprecated: Option<&'a str>,
+    is_async: bool,
+    nodiscard: bool,
+    visibility: Option<Visibility>,
+}
+
+impl<'a> Annotations<'a> {
+    /// The annotations of an item, from the whole doc comment of items
+    /// declared in one, like `---@class`.
+    fn read(source_file: &'a SourceFile, line: u64) -> Self {
+        Self::parse(source_file.doc_block(line))
+    }
+
+    /// The annotations of a member declared in code. Members declared in a
+    /// doc comment, like `---@field`, share the comment of their item, and
+    /// members of the item declared in other files aren't in this source.
+    fn read_member(source_file: &'a SourceFile, range: Range, name: &str) -> Option<Self> {
+        let line = range.start().line;
+        let text = source_file.text.lines().nth(usize::try_from(line).ok()?)?;
+        if text.trim_start().starts_with("---") {
+            return None
+        }
+
+        let defined = match definition_path(&text[utf16_offset(text, range.start().character)..]) {
+            "" => definition_path(text),
+            path => path,
+        };
+        let matches = defined == name
+            || defined.strip_suffix(name).is_some_and(|prefix| prefix.ends_with(['.', ':']));
+
+        matches.then(|| Self::parse(source_file.doc_comment(line)))
+    }
+
+    fn parse(comment: Vec<&'a str>) -> Self {
+        let has = |tag: &str| !tag_values(comment.iter().copied(), tag).is_empty();
+        let visibility = [
+            ("private", Visibility::Private),
+            ("protected", Visibility::Protected),
+            ("package", Visibility::Package),
+        ]
+        .into_iter()
+        .find(|(tag, _)| has(tag))
+        .map(|(_, visibility)| visibility);
+
+        Self {
+            deprecated: tag_values(comment.iter().copied(), "deprecated").into_iter().next(),
+            is_async: has("async"),
+            nodiscard: has("nodiscard"),
+            visibility,
+        }
+    }
+
+    fn deprecate(&self, deprecated: &mut Option<Deprecation>) {
+        if let Some(message) = self.deprecated {
+            *deprecated = Some(Deprecation {
+                message: (!message.is_empty()).then(|| message.to_string()),
+            });
+        }
+    }
+
+    fn restrict(&self, visibility: &mut Visibility) {
+        if let Some(annotated) = self.visibility {
+            *visibility = annotated;
+        }
+    }
+
+    fn annotate(&self, function: &mut Function) {
+        function.is_async |= self.is_async;
+        function.nodiscard |= self.nodiscard;
+    }
+}
+
+/// The name path being defined at the start of the code, like `song.load`
+/// in `function song.load() end`. Empty for an anonymous function.
+fn definition_path(code: &str) -> &str {
+    let mut code = code.trim_start();
+    for keyword in ["local ", "function "] {
+        code = code.strip_prefix(keyword).unwrap_or(code).trim_start();
+    }
+    let end = code
+        .find(|c: char| !(c.is_alphanumeric() || matches!(c, '_' | '.' | ':')))
+        .unwrap_or(code.len());
+
+    &code[..end]
+}
+
+/// The byte offset of a character offset in UTF-16 code units.
+fn utf16_offset(text: &str, character: u64) -> usize {
+    let mut units = 0;
+    text.char_indices()
+        .find(|(_, c)| {
+            units += c.len_utf16() as u64;
+            units > character
+        })
+        .map_or(text.len(), |(offset, _)| offset)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        doctree::{Class, DocItem, Table},
+        location::{FileUri, Position},
+        lua_type::LuaType,
+        testing::{function, item, range},
+    };
+
+    #[test]
+    fn read_member_annotations() {
+        let text = "local song = {}\n\n---@async\n---@nodiscard\n---@deprecated Use song.open\nfunction song.load() end\n---@private\nsong.path = \"\"\n";
+        let source_file = SourceFile::new(FileUri::parse("file:///library/song.lua").unwrap(), text.to_string());
+        let mut table = Table::default();
+        table.add_function(NamedFunction::new("load".to_string(), Function {
+            range: range(5),
+            ..function("function song.load()")
+        }));
+        table.add_field(Field::new("path".to_string(), None, LuaType::name("string"), range(7)));
+        table.add_field(Field::new("other".to_string(), None, LuaType::name("string"), range(4)));
+        let mut meta_file = MetaFile::new(source_file.uri.clone());
+        meta_file.add_item(item("song", DocItemEnum::Table(table)));
+
+        parse_annotations(&mut meta_file, &source_file);
+
+        let song = &meta_file.items["song"];
+        let DocItemEnum::Table(table) = &song.inner else { unreachable!() };
+        let load = &table.functions["load"];
+        assert_eq!(song.deprecated, None);
+        assert_eq!(load.deprecated.as_ref().and_then(|deprecated| deprecated.message()), Some("Use song.open"));
+        assert!(load.function.is_async && load.function.nodiscard);
+        assert_eq!(load.visibility, Visibility::Public);
+        assert_eq!(table.fields["path"].visibility, Visibility::Private);
+        assert_eq!(table.fields["other"].deprecated, None);
+    }
+
+    #[test]
+    fn match_member_definition() {
+        let text = "local song = {}\n---@private\nsong.volume = song.path\n---@deprecated\nfunction song:path() end\n";
+        let source_file = SourceFile::new(FileUri::parse("file:///library/song.lua").unwrap(), text.to_string());
+        let start = |line: u64, character: u64| Range::new(Position::unpack(line * 10000 + character), Position::unpack(line * 10000 + 20));
+
+        assert!(Annotations::read_member(&source_file, start(2, 0), "path").is_none());
+        assert!(Annotations::read_member(&source_file, start(2, 0), "volume").is_some());
+        assert!(Annotations::read_member(&source_file, start(4, 9), "path").is_some());
+        assert!(Annotations::read_member(&source_file, start(4, 0), "th").is_none());
+    }
+
+    #[test]
+    fn read_class_annotations() {
+        let text = "---A song.\n---@class Song\n---@deprecated Use Track\n---@field name string\nlocal Song = {}\n";
+        let source_file = SourceFile::new(FileUri::parse("file:///library/song.lua").unwrap(), text.to_string());
+        let mut meta_file = MetaFile::new(source_file.uri.clone());
+        meta_file.add_item(DocItem {
+            range: range(1),
+            ..item("Song", DocItemEnum::Class(Class::default()))
+        });
+
+        parse_annotations(&mut meta_file, &source_file);
+
+        let song = &meta_file.items["Song"];
+        assert_eq!(song.deprecated.as_ref().and_then(|deprecated| deprecated.message()), Some("Use Track"));
+    }
+}
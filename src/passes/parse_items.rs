@@ -1,13 +1,14 @@
-use crate::{doctree::{DocItem, MetaFile}, errors::*, workspace::SourceFile};
+use crate::{diagnostics::Diagnostics, doctree::{DocItem, MetaFile}, workspace::SourceFile};
 
-pub fn parse_items(meta_file: &mut MetaFile, source_file: &SourceFile) -> Result<()> {
+pub fn parse_items(meta_file: &mut MetaFile, source_file: &SourceFile, diagnostics: &mut Diagnostics) {
     for definition in source_file.definitions.iter() {
-        let item = DocItem::parse(definition)?;
-
-        if let Some(item) = item {
-            meta_file.add_item(item);
+        match DocItem::parse(definition) {
+            Ok(Some(item)) => meta_file.add_item(item),
+            Ok(None) => {},
+            Err(e) => {
+                let message = format!("Unable to document {}: {}", definition.name, e);
+                diagnostics.error(source_file, definition.defines.head.location.range, message);
+            },
         }
     }
-
-    Ok(())
 }
\ No newline at end of file
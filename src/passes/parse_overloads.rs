@@ -0,0 +1,42 @@
+use crate::{diagnostics::Diagnostics, doctree::{DocItemEnum, Function, Global, MetaFile}, lua_type::LuaType, workspace::SourceFile};
+
+/// Add the signatures declared with `---@overload`. LuaLS doesn't export
+/// these, so they are read from the source.
+pub fn parse_overloads(meta_file: &mut MetaFile, source_file: &SourceFile, diagnostics: &mut Diagnostics) {
+    for item in meta_file.items.values_mut() {
+        let functions: Vec<&mut Function> = match item.inner {
+            DocItemEnum::Class(ref mut class) => class.methods
+                .iter_mut()
+                .map(|method| &mut method.function)
+                .collect(),
+            DocItemEnum::Table(ref mut table) => table.functions
+                .values_mut()
+                .map(|function| &mut function.function)
+                .collect(),
+            DocItemEnum::Global(Global::Function(ref mut function)) => vec![function],
+            _ => continue,
+        };
+
+        for function in functions {
+            add_overloads(function, source_file, diagnostics);
+        }
+    }
+}
+
+fn add_overloads(function: &mut Function, source_file: &SourceFile, diagnostics: &mut Diagnostics) {
+    let signatures: Vec<LuaType> = std::iter::once(function.range)
+        .chain(function.overloads.iter().map(|overload| overload.range))
+        .flat_map(|range| source_file.annotations(range.start().line, "overload"))
+        .map(LuaType::from_view)
+        .collect();
+
+    for signature in signatures {
+        match function.overload(&signature) {
+            Some(overload) => function.add_overload(overload),
+            None => {
+                let message = format!("Skipping invalid overload {} for {}", signature, function.view);
+                diagnostics.warning(source_file, function.range, message);
+            },
+        }
+    }
+}
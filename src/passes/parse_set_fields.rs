@@ -1,65 +1,80 @@
 use itertools::Itertools;
 use log::debug;
 
-use crate::{doctree::{DocItemEnum, Field, Function, MetaFile, NamedFunction}, errors::*, json::{DefineType, ExtendsType}, workspace::SourceFile};
+use crate::{diagnostics::Diagnostics, doctree::{Deprecation, DocItemEnum, Field, Function, MetaFile, NamedFunction}, errors::*, json::{DefineType, Definition, ExtendsType}, lua_type::LuaType, workspace::SourceFile};
 
-pub fn parse_set_fields(meta_file: &mut MetaFile, source_file: &SourceFile) -> Result<()> {
+pub fn parse_set_fields(meta_file: &mut MetaFile, source_file: &SourceFile, diagnostics: &mut Diagnostics) {
     for definition in source_file.definitions.iter() {
         if ! matches!(definition.defines.head.define_type, DefineType::SetField | DefineType::SetIndex) {
             continue
         }
 
-        let extends = definition.defines.head.extends
-            .first()
-            .ok_or_else(|| anyhow!("Expected an extends for setfield at {:?}", definition.defines.head.location.range))?;
-        
-        let (table_name, field_name) = definition.name.splitn(2, ".").collect_tuple()
-            .ok_or_else(|| anyhow!("Invalid setfield name {}", definition.name))?;
-
-        // Usually it's a class, which already captured this via "fields".
-        // Sometimes it's naming a table "foo.bar", when "foo" was declared in
-        // a parent module. We don't want to capture either of these.
-        if ! meta_file.items.contains_key(table_name) {
-            debug!("Skipping missing table reference {}", definition.name);
-            continue
+        if let Err(e) = parse_set_field(meta_file, definition) {
+            diagnostics.warning(source_file, definition.defines.head.location.range, e);
         }
+    }
+}
+
+fn parse_set_field(meta_file: &mut MetaFile, definition: &Definition) -> Result<()> {
+    let extends = definition.defines.head.extends
+        .first()
+        .ok_or_else(|| anyhow!("Expected an extends for setfield {}", definition.name))?;
+    
+    let (table_name, field_name) = definition.name.splitn(2, ".").collect_tuple()
+        .ok_or_else(|| anyhow!("Invalid setfield name {}", definition.name))?;
 
-        let table = meta_file.items.get_mut(table_name)
-            .ok_or_else(|| anyhow!("missing table"))?;
+    // Usually it's a class, which already captured this via "fields".
+    // Sometimes it's naming a table "foo.bar", when "foo" was declared in
+    // a parent module. We don't want to capture either of these.
+    if ! meta_file.items.contains_key(table_name) {
+        debug!("Skipping missing table reference {}", definition.name);
+        return Ok(())
+    }
+
+    let table = meta_file.items.get_mut(table_name)
+        .ok_or_else(|| anyhow!("missing table"))?;
 
-        debug!("Setting table {:?} field {:?}", table_name.to_string(), field_name.to_string());
+    debug!("Setting table {:?} field {:?}", table_name.to_string(), field_name.to_string());
 
-        match table.inner {
-            DocItemEnum::Table(ref mut table) => {
-                match extends.extends_type {
-                    ExtendsType::Binary |
-                    ExtendsType::Integer |
-                    ExtendsType::Nil |
-                    ExtendsType::Number |
-                    ExtendsType::String |
-                    ExtendsType::Table => {
-                        let field = Field {
-                            name: field_name.to_string(),
-                            description: definition.rawdesc.clone(),
-                            lua_type: extends.view.clone(),
-                        };
-                        debug!("Adding table field {:?}", field_name.to_string());
-                        table.add_field(field);
-                    },
-                    ExtendsType::Function => {
-                        let method = NamedFunction {
-                            name: field_name.to_string(),
-                            function: Function::parse(extends)?,
-                        };
+    match table.inner {
+        DocItemEnum::Table(ref mut table) => {
+            match extends.extends_type {
+                ExtendsType::Binary |
+                ExtendsType::Integer |
+                ExtendsType::Nil |
+                ExtendsType::Number |
+                ExtendsType::String |
+                ExtendsType::Table => {
+                    let field = Field::new(
+                        field_name.to_string(),
+                        definition.rawdesc.clone(),
+                        LuaType::from_extends(extends),
+                        definition.defines.head.location.range,
+                    );
+                    debug!("Adding table field {:?}", field_name.to_string());
+                    table.add_field(field);
+                },
+                ExtendsType::Function => {
+                    // Each define of the same name is another overload
+                    let functions = definition.defines
+                        .iter()
+                        .flat_map(|define| define.extends.iter())
+                        .filter(|extends| extends.extends_type == ExtendsType::Function);
+
+                    for extends in functions {
+                        let mut method = NamedFunction::new(field_name.to_string(), Function::parse(extends)?);
+                        method.deprecated = Deprecation::from_flag(extends.deprecated);
 
                         table.add_function(method);
                     }
-                    _ => bail!("Unexpected setfield type {:?}", extends.extends_type)
                 }
-            },
-            DocItemEnum::Class(_) => {}, // Ignore, already set via "fields" attribute
-            _ => bail!("Setting field {} for non-table {}", field_name, table.name),
-        }
+                // Reported when building the docs
+                ExtendsType::Other(_) => {},
+                _ => bail!("Unexpected setfield type {:?}", extends.extends_type)
+            }
+        },
+        DocItemEnum::Class(_) => {}, // Ignore, already set via "fields" attribute
+        _ => bail!("Setting field {} for non-table {}", field_name, table.name),
     }
 
     Ok(())
@@ -1,38 +1,115 @@
 use itertools::Itertools;
 use log::debug;
 
-use crate::{doctree::{DocItemEnum, Field, Function, MetaFile, NamedFunction}, errors::*, json::{DefineType, ExtendsType}, workspace::SourceFile};
+use crate::{diagnostics::Diagnostics, doctree::{DocItemEnum, EnumMember, MetaFile}, errors::*, json::{DefineType, Definition}, location::{read_range, Position, Range}, workspace::SourceFile};
 
-pub fn parse_table_fields(meta_file: &mut MetaFile, source_file: &SourceFile) -> Result<()> {
+pub fn parse_table_fields(meta_file: &mut MetaFile, source_file: &SourceFile, diagnostics: &mut Diagnostics) {
     for definition in source_file.definitions.iter() {
         if ! matches!(definition.defines.head.define_type, DefineType::TableField) {
             continue
         }
 
-        let (enum_name, field_name) = definition.name.splitn(2, ".").collect_tuple()
-            .ok_or_else(|| anyhow!("Invalid tablefield name {}", definition.name))?;
-
-        if ! meta_file.items.contains_key(enum_name) {
-            debug!("Skipping missing enum reference {}", definition.name);
-            continue
+        if let Err(e) = parse_table_field(meta_file, source_file, definition) {
+            diagnostics.warning(source_file, definition.defines.head.location.range, e);
         }
+    }
+}
+
+fn parse_table_field(meta_file: &mut MetaFile, source_file: &SourceFile, definition: &Definition) -> Result<()> {
+    let (enum_name, field_name) = definition.name.splitn(2, ".").collect_tuple()
+        .ok_or_else(|| anyhow!("Invalid tablefield name {}", definition.name))?;
+
+    if ! meta_file.items.contains_key(enum_name) {
+        debug!("Skipping missing enum reference {}", definition.name);
+        return Ok(())
+    }
+
+    let lua_enum = meta_file.items.get_mut(enum_name)
+        .ok_or_else(|| anyhow!("missing enum"))?;
+
+    debug!("Setting enum {:?} field {:?}", enum_name.to_string(), field_name.to_string());
+
+    match lua_enum.inner {
+        DocItemEnum::Enum(ref mut lua_enum) => {
+            // LuaLS doesn't export the values, so read them from the source.
+            let range = definition.defines.head.location.range;
+            let value = read_field_value(&source_file.text, &range);
+
+            lua_enum.add_field(EnumMember::new(
+                field_name.to_string(),
+                definition.rawdesc.clone(),
+                value,
+                range,
+            ))
+        },
+        _ => bail!("Setting field {} for non enum {}", field_name, lua_enum.name),
+    }
+
+    Ok(())
+}
 
-        let lua_enum = meta_file.items.get_mut(enum_name)
-            .ok_or_else(|| anyhow!("missing enum"))?;
+/// Read the value assigned to a table field. The range may only cover the
+/// key, so the rest of the last line is read too.
+fn read_field_value(text: &str, range: &Range) -> Option<String> {
+    let (start, end) = range.bounds();
+    let line_end = Position { line: end.line, character: u64::MAX };
+    let source = read_range(text, &Range::new(start, line_end));
 
-        debug!("Setting enum {:?} field {:?}", enum_name.to_string(), field_name.to_string());
+    let (_, expr) = source.split_once('=')?;
 
-        match lua_enum.inner {
-            DocItemEnum::Enum(ref mut lua_enum) => {
-                lua_enum.add_field(Field {
-                    name: field_name.to_string(),
-                    description: definition.rawdesc.clone(),
-                    lua_type: "".to_string(), // TODO: no types in docs?
-                })
+    // The value ends at the next separator outside of any string or brackets
+    let mut depth = 0;
+    let mut quote = None;
+    let mut escaped = false;
+    let mut len = expr.len();
+
+    for (i, c) in expr.char_indices() {
+        if let Some(q) = quote {
+            if c == q && !escaped {
+                quote = None;
+            }
+            escaped = c == '\\' && !escaped;
+            continue
+        }
+
+        match c {
+            '"' | '\'' => quote = Some(c),
+            '(' | '{' | '[' => depth += 1,
+            ')' | '}' | ']' if depth > 0 => depth -= 1,
+            ',' | ';' if depth > 0 => {},
+            ')' | '}' | ']' | ',' | ';' => {
+                len = i;
+                break
             },
-            _ => bail!("Setting field {} for non enum {}", field_name, lua_enum.name),
+            '-' if depth == 0 && expr[i..].starts_with("--") => {
+                len = i;
+                break
+            },
+            _ => {},
         }
     }
 
-    Ok(())
-}
\ No newline at end of file
+    let value = expr[..len].trim();
+
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn range(start: u64, end: u64) -> Range {
+        Range::new(Position::unpack(start), Position::unpack(end))
+    }
+
+    #[test]
+    fn read_field_values() {
+        let text = "local COLORS = {\n\tblack = 0,\n\tred = \"a, b\" -- red\n\tgreen = { 1, 2 }}";
+
+        assert_eq!(read_field_value(text, &range(10001, 10006)), Some("0".to_string()));
+        assert_eq!(read_field_value(text, &range(10001, 10010)), Some("0".to_string()));
+        assert_eq!(read_field_value(text, &range(20001, 20004)), Some("\"a, b\"".to_string()));
+        assert_eq!(read_field_value(text, &range(30001, 30006)), Some("{ 1, 2 }".to_string()));
+        assert_eq!(read_field_value("{ 1, 2 }", &range(2, 3)), None);
+    }
+}
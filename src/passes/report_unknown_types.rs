@@ -0,0 +1,49 @@
+use crate::{diagnostics::Diagnostics, json::{ArgType, DefineType, Extends, ExtendsType, FieldType}, location::Range, workspace::SourceFile};
+
+/// Warn about definitions using types added by a newer version of LuaLS. These
+/// are skipped, or documented as well as possible.
+pub fn report_unknown_types(source_file: &SourceFile, diagnostics: &mut Diagnostics) {
+    for definition in source_file.definitions.iter() {
+        let mut report = |kind: &str, tag: &str, range: Range, raw: &Option<String>| {
+            let message = format!(
+                "Unknown LuaLS {} type {:?} in {}: {}",
+                kind,
+                tag,
+                definition.name,
+                raw.as_deref().unwrap_or_default()
+            );
+            diagnostics.warning(source_file, range, message);
+        };
+
+        for define in definition.defines.iter() {
+            if let DefineType::Other(tag) = &define.define_type {
+                report("define", tag, define.location.range, &define.raw);
+            }
+            for extends in define.extends.iter() {
+                report_extends(extends, &mut report);
+            }
+        }
+
+        // Fields may be declared in another file
+        for field in definition.fields.iter().filter(|field| field.location.file == source_file.uri) {
+            if let FieldType::Other(tag) = &field.field_type {
+                report("field", tag, field.location.range, &field.raw);
+            }
+            report_extends(&field.extends, &mut report);
+        }
+    }
+}
+
+fn report_extends<F>(extends: &Extends, report: &mut F)
+where
+    F: FnMut(&str, &str, Range, &Option<String>),
+{
+    if let ExtendsType::Other(tag) = &extends.extends_type {
+        report("extends", tag, extends.range, &extends.raw);
+    }
+    for arg in extends.args.iter() {
+        if let ArgType::Other(tag) = &arg.arg_type {
+            report("argument", tag, arg.range, &arg.raw);
+        }
+    }
+}
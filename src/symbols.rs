@@ -0,0 +1,297 @@
+//! An index of every documented item, used to link to an item's definition.
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Component, Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    doctree::{DocItem, DocItemEnum, DocTree, MetaFile, PageGranularity},
+    errors::*,
+    location::FileUri,
+};
+
+/// The kind of item a symbol refers to.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[non_exhaustive]
+pub enum SymbolKind {
+    Class,
+    Table,
+    TypeAlias,
+    Enum,
+    Global,
+    Field,
+    Function,
+}
+
+impl From<&DocItemEnum> for SymbolKind {
+    fn from(inner: &DocItemEnum) -> Self {
+        match inner {
+            DocItemEnum::Class(_) => Self::Class,
+            DocItemEnum::Table(_) => Self::Table,
+            DocItemEnum::TypeAlias(_) => Self::TypeAlias,
+            DocItemEnum::Enum(_) => Self::Enum,
+            DocItemEnum::Global(_) => Self::Global,
+        }
+    }
+}
+
+/// Where a documented item is rendered.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Symbol {
+    pub(crate) name: String,
+    pub(crate) kind: SymbolKind,
+    /// The chapter path, relative to the book source directory.
+    pub(crate) path: PathBuf,
+    /// The id of the item's heading within the chapter.
+    pub(crate) anchor: String,
+}
+
+impl Symbol {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn kind(&self) -> SymbolKind {
+        self.kind
+    }
+
+    /// The chapter path, relative to the book source directory.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The id of the item's heading within the chapter.
+    pub fn anchor(&self) -> &str {
+        &self.anchor
+    }
+
+    pub(crate) fn for_item(item: &DocItem, path: &Path) -> Self {
+        Self {
+            name: item.name.clone(),
+            kind: (&item.inner).into(),
+            path: path.to_path_buf(),
+            anchor: item.name.clone(),
+        }
+    }
+
+    /// A member of an item, named by the item name and member name joined with
+    /// a `.`.
+    pub(crate) fn for_member(item: &DocItem, member: &str, kind: SymbolKind, path: &Path) -> Self {
+        let name = format!("{}.{}", item.name, member);
+
+        Self {
+            anchor: name.clone(),
+            name,
+            kind,
+            path: path.to_path_buf(),
+        }
+    }
+
+    /// A link to this symbol from the given chapter.
+    pub fn link_from(&self, page: &Path) -> String {
+        format!("{}#{}", relative_path(page, &self.path).display(), self.anchor)
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct SymbolIndex {
+    symbols: BTreeMap<String, Symbol>,
+    pages: HashMap<FileUri, PathBuf>,
+}
+
+impl SymbolIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Index every item in the tree, with a page for each file. Chapter paths
+    /// are relative to the definitions path.
+    pub fn build(tree: &DocTree, base: &Path) -> Result<Self> {
+        Self::build_with_pages(tree, base, PageGranularity::File)
+    }
+
+    /// Index every item in the tree, with pages split up at the given
+    /// granularity.
+    pub fn build_with_pages(tree: &DocTree, base: &Path, granularity: PageGranularity) -> Result<Self> {
+        let mut index = Self::new();
+        let mut result = Ok(());
+
+        tree.for_each(|file| {
+            if result.is_ok() {
+                result = index.insert_file(file, base, granularity);
+            }
+        });
+
+        result.map(|_| index)
+    }
+
+    fn insert_file(&mut self, file: &MetaFile, base: &Path, granularity: PageGranularity) -> Result<()> {
+        let path = file.page_path(base)?;
+
+        let mut item_paths = HashMap::new();
+        for item in file.page_items(granularity) {
+            item_paths.insert(item.name.as_str(), file.item_page_path(base, item)?);
+        }
+
+        let item_path = |item: &DocItem| item_paths.get(item.name.as_str()).unwrap_or(&path).clone();
+        for item in file.items.values() {
+            self.insert(Symbol::for_item(item, &item_path(item)));
+        }
+        for item in file.items.values() {
+            self.insert_members(item, &item_path(item));
+        }
+        self.pages.insert(file.uri.clone(), path);
+
+        Ok(())
+    }
+
+    pub fn insert(&mut self, symbol: Symbol) {
+        self.symbols.insert(symbol.name.clone(), symbol);
+    }
+
+    /// Index the fields and functions of a class or table, unless an item
+    /// already has the same name.
+    fn insert_members(&mut self, item: &DocItem, path: &Path) {
+        let (fields, functions): (Vec<&str>, Vec<&str>) = match &item.inner {
+            DocItemEnum::Class(class) => (
+                class.fields.iter().map(|field| field.name.as_str()).collect(),
+                class.methods.iter().map(|method| method.name.as_str()).collect(),
+            ),
+            DocItemEnum::Table(table) => (
+                table.fields.keys().map(String::as_str).collect(),
+                table.functions.keys().map(String::as_str).collect(),
+            ),
+            _ => return,
+        };
+
+        let members = fields
+            .into_iter()
+            .map(|name| (name, SymbolKind::Field))
+            .chain(functions.into_iter().map(|name| (name, SymbolKind::Function)));
+
+        for (name, kind) in members {
+            let symbol = Symbol::for_member(item, name, kind, path);
+            self.symbols.entry(symbol.name.clone()).or_insert(symbol);
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Symbol> {
+        self.symbols.get(name)
+    }
+
+    /// Find a symbol by name, also accepting a method written as
+    /// `Class:method`.
+    pub fn resolve(&self, name: &str) -> Option<&Symbol> {
+        self.get(name).or_else(|| {
+            let (item, member) = name.rsplit_once(':')?;
+            self.get(&format!("{}.{}", item, member))
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.symbols.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.symbols.is_empty()
+    }
+
+    /// The chapter path for the given file.
+    pub fn page(&self, uri: &FileUri) -> Option<&Path> {
+        self.pages.get(uri).map(|path| path.as_path())
+    }
+}
+
+/// The path to `to` relative to the directory containing `from`.
+pub fn relative_path(from: &Path, to: &Path) -> PathBuf {
+    let from_dir: Vec<Component> = from
+        .parent()
+        .map(|dir| dir.components().collect())
+        .unwrap_or_default();
+    let to: Vec<Component> = to.components().collect();
+
+    let common = from_dir
+        .iter()
+        .zip(to.iter())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let mut path = PathBuf::new();
+    for _ in common..from_dir.len() {
+        path.push("..");
+    }
+    for component in &to[common..] {
+        path.push(component);
+    }
+
+    path
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        doctree::{Class, Field, MetaFile},
+        lua_type::LuaType,
+        testing::{item, range},
+    };
+
+    fn class(name: &str) -> DocItem {
+        item(name, DocItemEnum::Class(Class::default()))
+    }
+
+    #[test]
+    fn build_index() -> Result<()> {
+        let base = PathBuf::from("/library");
+        let mut parent = MetaFile::new(FileUri::parse("file:///library/renoise.lua")?);
+        parent.add_item(class("renoise"));
+        let mut child = MetaFile::new(FileUri::parse("file:///library/renoise/application.lua")?);
+        child.add_item(class("renoise.Application"));
+        parent.children.push(child);
+
+        let mut tree = DocTree::new();
+        tree.add_item(parent);
+
+        let index = SymbolIndex::build(&tree, &base)?;
+        let symbol = index.get("renoise.Application").unwrap();
+
+        assert_eq!(index.len(), 2);
+        assert_eq!(symbol.kind, SymbolKind::Class);
+        assert_eq!(symbol.path, PathBuf::from("renoise/application.md"));
+        assert_eq!(symbol.anchor, "renoise.Application");
+        assert_eq!(symbol.link_from(Path::new("renoise.md")), "renoise/application.md#renoise.Application");
+        assert_eq!(index.get("renoise").unwrap().link_from(Path::new("renoise/application.md")), "../renoise.md#renoise");
+
+        Ok(())
+    }
+
+    #[test]
+    fn relative_paths() {
+        assert_eq!(relative_path(Path::new("a.md"), Path::new("a.md")), PathBuf::from("a.md"));
+        assert_eq!(relative_path(Path::new("a/b.md"), Path::new("a/c.md")), PathBuf::from("c.md"));
+        assert_eq!(relative_path(Path::new("a/b/c.md"), Path::new("d.md")), PathBuf::from("../../d.md"));
+    }
+
+    #[test]
+    fn index_members() -> Result<()> {
+        let mut bit = class("bit");
+        if let DocItemEnum::Class(class) = &mut bit.inner {
+            class.fields.push(Field::new("bits".to_string(), None, LuaType::name("integer"), range(0)));
+        }
+        let mut file = MetaFile::new(FileUri::parse("file:///library/bit.lua")?);
+        file.add_item(bit);
+        file.add_item(class("bit.bits"));
+
+        let mut tree = DocTree::new();
+        tree.add_item(file);
+        let index = SymbolIndex::build(&tree, Path::new("/library"))?;
+
+        // Items take precedence over members with the same name
+        assert_eq!(index.get("bit.bits").unwrap().kind, SymbolKind::Class);
+        assert!(index.resolve("bit:bits").is_some());
+        assert!(index.resolve("bit:tohex").is_none());
+
+        Ok(())
+    }
+}
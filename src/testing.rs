@@ -0,0 +1,36 @@
+//! Fixtures shared by the unit tests.
+
+use crate::{
+    doctree::{DocItem, DocItemEnum, Function},
+    location::{Position, Range},
+};
+
+/// A short range at the start of the given line.
+pub(crate) fn range(line: u64) -> Range {
+    Range::new(Position::unpack(line * 10000), Position::unpack(line * 10000 + 5))
+}
+
+/// An undocumented item on the first line.
+pub(crate) fn item(name: &str, inner: DocItemEnum) -> DocItem {
+    DocItem {
+        name: name.to_string(),
+        description: None,
+        range: range(0),
+        deprecated: None,
+        inner,
+    }
+}
+
+/// An undocumented function on the first line, without arguments or returns.
+pub(crate) fn function(view: &str) -> Function {
+    Function {
+        description: None,
+        view: view.to_string(),
+        range: range(0),
+        arguments: Vec::new(),
+        returns: Vec::new(),
+        overloads: Vec::new(),
+        is_async: false,
+        nodiscard: false,
+    }
+}
@@ -4,23 +4,26 @@
 use std::collections::HashMap;
 use std::fs::File;
 use std::io::Read;
+use std::path::Path;
 
 use itertools::Itertools;
 use log::*;
 use serde::{Deserialize, Serialize};
 
 
-use crate::json::Definition;
+use crate::json::{generate_json_docs, read_json_docs, Definition};
 use crate::errors::*;
-use crate::location::FileUri;
+use crate::location::{FileUri, PathRemap};
 
 /// A root folder containing LuaCats definition files.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Workspace {
     /// Root URI for the workspace.
-    pub root: FileUri,
+    pub(crate) root: FileUri,
     /// All files within the workspace.
-    pub files: HashMap<FileUri,SourceFile>,
+    pub(crate) files: HashMap<FileUri,SourceFile>,
+    /// Rules for rewriting definition file paths, applied in order.
+    pub(crate) remaps: Vec<PathRemap>,
 }
 
 impl Workspace {
@@ -28,11 +31,20 @@ impl Workspace {
         Self {
             root,
             files: HashMap::new(),
+            remaps: Vec::new(),
         }
     }
 
-    pub fn load(&mut self, docs: Vec<Definition>) -> Result<()> {
-        for doc in docs.into_iter() {
+    pub fn add_remap(&mut self, remap: PathRemap) {
+        self.remaps.push(remap);
+    }
+
+    pub(crate) fn load(&mut self, docs: Vec<Definition>) -> Result<()> {
+        for mut doc in docs.into_iter() {
+            if !self.remaps.is_empty() {
+                doc.map_files(|uri| uri.remap(&self.remaps).unwrap_or_else(|| uri.clone()));
+            }
+
             let uris: Vec<FileUri> = doc
                 .defines
                 .iter()
@@ -46,18 +58,43 @@ impl Workspace {
                     continue
                 }
 
-                if !self.files.contains_key(&uri) {
-                    let file = SourceFile::open(&uri)?;
+                if !self.files.contains_key(uri) {
+                    let file = SourceFile::open(uri)?;
                     self.files.insert(uri.clone(), file);
                 }
 
-                let file = self.files.get_mut(&uri).unwrap();
+                let file = self.files.get_mut(uri).unwrap();
                 file.add_definition(doc.clone())?;
             }
         }
 
         Ok(())
     }
+
+    /// Load the definitions from a previously exported `doc.json` file.
+    pub fn load_json_file(&mut self, path: &Path) -> Result<()> {
+        let docs = read_json_docs(path)?;
+        debug!("Read {} definitions from {:?}", docs.len(), path);
+
+        self.load(docs)
+    }
+
+    /// Run LuaLS on the workspace root and load the definitions it exports.
+    pub fn load_luals(&mut self) -> Result<()> {
+        let docs = generate_json_docs(&self.root.to_file_path()?)?;
+        debug!("Generated {} definitions", docs.len());
+
+        self.load(docs)
+    }
+
+    /// The number of definition files loaded.
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
 }
 
 impl<'a> IntoIterator for &'a Workspace {
@@ -75,7 +112,6 @@ impl<'a> IntoIterator for &'a Workspace {
                         a.uri.file_name()
                             .cmp(&b.uri.file_name()))
             )
-            .into_iter()
     }
 }
 
@@ -112,6 +148,48 @@ impl SourceFile {
         })
     }
 
+    /// The `---` doc comment lines directly above the given line, with the
+    /// dashes removed.
+    pub fn doc_comment(&self, line: u64) -> Vec<&str> {
+        let line = usize::try_from(line).expect("overflow");
+        let mut comment: Vec<&str> = self.text
+            .lines()
+            .take(line)
+            .collect::<Vec<_>>()
+            .into_iter()
+            .rev()
+            .map(|l| l.trim_start())
+            .take_while(|l| l.starts_with("---"))
+            .map(|l| &l[3..])
+            .collect();
+        comment.reverse();
+
+        comment
+    }
+
+    /// The `---` doc comment documenting the definition on the given line,
+    /// with the dashes removed. When the line is itself part of a doc comment,
+    /// like `---@class`, this is the whole comment around it, up to the
+    /// neighbouring `---@class`, `---@alias` or `---@enum`.
+    pub fn doc_block(&self, line: u64) -> Vec<&str> {
+        let line = usize::try_from(line).expect("overflow");
+        let lines: Vec<&str> = self.text.lines().map(|l| l.trim_start()).collect();
+        if !lines.get(line).is_some_and(|l| l.starts_with("---")) {
+            return self.doc_comment(line as u64);
+        }
+
+        let in_block = |l: &&&str| l.starts_with("---") && !is_type_definition(&l[3..]);
+        let above = lines[..line].iter().rev().take_while(in_block).count();
+        let below = lines[line + 1..].iter().take_while(in_block).count();
+
+        lines[line - above..=line + below].iter().map(|l| &l[3..]).collect()
+    }
+
+    /// The values of each `---@<tag>` annotation directly above the given line.
+    pub fn annotations(&self, line: u64, tag: &str) -> Vec<&str> {
+        tag_values(self.doc_comment(line), tag)
+    }
+
     pub fn add_definition(&mut self, mut definition: Definition) -> Result<()> {
         definition.defines = definition.defines
             .into_iter()
@@ -124,4 +202,81 @@ impl SourceFile {
 
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+/// The values of each `@<tag>` annotation in the doc comment lines.
+pub(crate) fn tag_values<'a>(comment: impl IntoIterator<Item = &'a str>, tag: &str) -> Vec<&'a str> {
+    comment
+        .into_iter()
+        .filter_map(|l| l.strip_prefix('@'))
+        .filter_map(|l| {
+            let (name, value) = l.split_once(char::is_whitespace).unwrap_or((l, ""));
+            (name == tag).then(|| value.trim())
+        })
+        .collect()
+}
+
+/// Whether the doc comment line starts a new type.
+fn is_type_definition(comment: &str) -> bool {
+    ["@class", "@alias", "@enum"]
+        .iter()
+        .any(|tag| comment.strip_prefix(tag).is_some_and(|rest| rest.starts_with(char::is_whitespace)))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn load_remapped_json_file() -> Result<()> {
+        let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        let root: FileUri = manifest_dir.join("testdata").try_into()?;
+
+        let mut workspace = Workspace::new(root);
+        workspace.add_remap(PathRemap::new("/Users/matt/Code/mooncats", &manifest_dir));
+        workspace.load_json_file(&manifest_dir.join("testdata/doc.json"))?;
+
+        let uri: FileUri = manifest_dir.join("testdata/test.lua").try_into()?;
+        let file = workspace.files.get(&uri).expect("missing test.lua");
+
+        assert_eq!(workspace.files.len(), 1);
+        assert_eq!(file.definitions.len(), 8);
+
+        Ok(())
+    }
+
+    #[test]
+    fn load_json_file_without_remap() -> Result<()> {
+        let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        let root: FileUri = manifest_dir.join("testdata").try_into()?;
+
+        let mut workspace = Workspace::new(root);
+        workspace.load_json_file(&manifest_dir.join("testdata/doc.json"))?;
+
+        assert!(workspace.files.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn read_annotations() {
+        let text = "---Converts to hex.\n---@param x integer\n---@overload fun(x: integer): string\n  ---@nodiscard\nfunction bit.tohex(x, n) end";
+        let file = SourceFile::new(FileUri::parse("file:///library/bit.lua").unwrap(), text.to_string());
+
+        assert_eq!(file.doc_comment(4).len(), 4);
+        assert_eq!(file.annotations(4, "overload"), vec!["fun(x: integer): string"]);
+        assert_eq!(file.annotations(4, "nodiscard"), vec![""]);
+        assert!(file.annotations(1, "param").is_empty());
+    }
+
+    #[test]
+    fn read_doc_block() {
+        let text = "---@alias Id integer\n---A song.\n---@class Song\n---@deprecated\n---@field id Id\n---@class Other\nlocal Song = {}";
+        let file = SourceFile::new(FileUri::parse("file:///library/song.lua").unwrap(), text.to_string());
+
+        assert_eq!(file.doc_block(2), vec!["A song.", "@class Song", "@deprecated", "@field id Id"]);
+        assert_eq!(file.doc_block(0), vec!["@alias Id integer", "A song."]);
+        assert_eq!(file.doc_block(6), file.doc_comment(6));
+    }
+}
@@ -0,0 +1,64 @@
+//! Builds and renders the test data using only the public API.
+
+use std::path::Path;
+use std::sync::Arc;
+
+use mooncats::{
+    build_docs_with_diagnostics, errors::Result, DocItemEnum, FileUri, Global, LuaType, MarkdownRenderer, PathRemap,
+    SymbolIndex, Workspace,
+};
+
+#[test]
+fn build_and_render_docs() -> Result<()> {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let library = root.join("testdata");
+
+    let mut workspace = Workspace::new(FileUri::try_from(library.clone())?);
+    let remap = PathRemap::new("/Users/matt/Code/mooncats", root);
+    assert_eq!(remap.to(), root);
+    workspace.add_remap(remap);
+    workspace.load_json_file(&root.join("testdata/doc.json"))?;
+
+    let (tree, diagnostics) = build_docs_with_diagnostics(workspace);
+    for diagnostic in diagnostics.iter() {
+        assert!(diagnostic.location().file().to_file_path()?.starts_with(&library), "{}", diagnostic);
+        assert!(!diagnostic.message().is_empty());
+    }
+
+    let file = &tree.files()[0];
+    assert_eq!(file.uri().file_name(), "test.lua");
+
+    let class = file.item("AClass").and_then(|item| item.as_class()).expect("AClass");
+    assert_eq!(class.fields()[0].name(), "foo");
+    assert_eq!(class.fields()[0].lua_type(), &LuaType::name("string"));
+    let do_stuff = &class.methods()[0];
+    assert_eq!(do_stuff.name(), "do_stuff");
+    assert_eq!(do_stuff.function().description(), Some("Does stuff"));
+
+    let mut globals = Vec::new();
+    for item in file.items() {
+        if let DocItemEnum::Global(Global::Primitive(primitive)) = item.kind() {
+            globals.push((item.name(), primitive.primitive_type().to_string()));
+        }
+    }
+    assert!(globals.contains(&("global_int", "integer".to_string())), "{:?}", globals);
+
+    let table = file.item("global_table").and_then(|item| item.as_table()).expect("global_table");
+    assert!(table.fields().any(|field| field.name() == "set_field"));
+    let argument = &do_stuff.function().arguments()[0];
+    assert_eq!((argument.name(), argument.arg_type()), (Some("self"), &LuaType::name("self")));
+
+    if let Ok(LuaType::Function { params, .. }) = LuaType::parse("fun(x?: integer)") {
+        assert_eq!((params[0].name(), params[0].optional()), (Some("x"), true));
+        assert_eq!(params[0].param_type(), Some(&LuaType::name("integer")));
+    } else {
+        panic!("expected a function type");
+    }
+
+    let symbols = Arc::new(SymbolIndex::build(&tree, &library)?);
+    let page = MarkdownRenderer::with_symbols(symbols).render_meta(file)?;
+    assert!(page.contains("### AClass"), "{}", page);
+    assert!(page.contains("AClass:do_stuff()"), "{}", page);
+
+    Ok(())
+}